@@ -0,0 +1,583 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::filter::parse_duration_ms;
+use crate::report::{self, GroupBy, GroupRow, GroupSpec, Report, ReportOptions};
+
+/// What a budget rule measures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Requests,
+    TotalBytes,
+    TotalTime,
+    /// Time of the slowest single request.
+    RequestTime,
+    /// Request time percentile, written as `p95_time`.
+    TimePercentile(f64),
+    ThirdPartyRequests,
+    ThirdPartyBytes,
+    /// Responses in a status class, written as `status_5xx`.
+    StatusClass(u8),
+}
+
+const METRIC_NAMES: &str = "requests, total_bytes, total_time, request_time, p<N>_time, \
+                            third_party_requests, third_party_bytes, status_<N>xx";
+
+impl Metric {
+    pub fn parse(name: &str) -> Result<Self> {
+        let metric = match name.trim() {
+            "requests" => Metric::Requests,
+            "total_bytes" => Metric::TotalBytes,
+            "total_time" => Metric::TotalTime,
+            "request_time" => Metric::RequestTime,
+            "third_party_requests" => Metric::ThirdPartyRequests,
+            "third_party_bytes" => Metric::ThirdPartyBytes,
+            other => {
+                if let Some(p) = other
+                    .strip_prefix('p')
+                    .and_then(|rest| rest.strip_suffix("_time"))
+                {
+                    match p.parse::<f64>() {
+                        Ok(p) if p > 0.0 && p <= 100.0 => Metric::TimePercentile(p),
+                        _ => bail!("percentile must be in (0, 100]: {}", other),
+                    }
+                } else if let Some(class) = other
+                    .strip_prefix("status_")
+                    .and_then(|rest| rest.strip_suffix("xx"))
+                {
+                    match class.parse() {
+                        Ok(class @ 1..=5) => Metric::StatusClass(class),
+                        _ => bail!("invalid status class (expected e.g. status_5xx): {}", other),
+                    }
+                } else {
+                    bail!(
+                        "unknown metric '{}' (expected one of: {})",
+                        other,
+                        METRIC_NAMES
+                    );
+                }
+            }
+        };
+        Ok(metric)
+    }
+
+    pub fn unit(self) -> Unit {
+        match self {
+            Metric::Requests | Metric::ThirdPartyRequests | Metric::StatusClass(_) => Unit::Count,
+            Metric::TotalBytes | Metric::ThirdPartyBytes => Unit::Bytes,
+            Metric::TotalTime | Metric::RequestTime | Metric::TimePercentile(_) => Unit::Ms,
+        }
+    }
+
+    fn is_third_party(self) -> bool {
+        matches!(self, Metric::ThirdPartyRequests | Metric::ThirdPartyBytes)
+    }
+
+    /// Whether the metric can be evaluated for each group of a `per` rule.
+    fn supports_groups(self) -> bool {
+        matches!(
+            self,
+            Metric::Requests
+                | Metric::TotalBytes
+                | Metric::TotalTime
+                | Metric::RequestTime
+                | Metric::TimePercentile(_)
+        )
+    }
+
+    fn measure(self, report: &Report) -> f64 {
+        match self {
+            Metric::Requests => report.entries as f64,
+            Metric::TotalBytes => report.total_bytes as f64,
+            Metric::TotalTime => report.total_time_ms,
+            Metric::RequestTime => report.max_time_ms,
+            Metric::TimePercentile(p) => lookup_percentile(&report.time_percentiles, p),
+            Metric::ThirdPartyRequests => report
+                .third_party
+                .as_ref()
+                .map_or(0.0, |share| share.requests as f64),
+            Metric::ThirdPartyBytes => report
+                .third_party
+                .as_ref()
+                .map_or(0.0, |share| share.bytes as f64),
+            Metric::StatusClass(class) => report
+                .status_classes
+                .get(&format!("{}xx", class))
+                .map_or(0.0, |count| *count as f64),
+        }
+    }
+
+    fn measure_group(self, group: &GroupRow) -> f64 {
+        match self {
+            Metric::Requests => group.count as f64,
+            Metric::TotalBytes => group.total_bytes as f64,
+            Metric::TotalTime => group.total_time_ms,
+            Metric::RequestTime => group.max_time_ms,
+            Metric::TimePercentile(p) => lookup_percentile(&group.percentiles, p),
+            _ => unreachable!("metric {} is not evaluated per group", self),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Metric::Requests => write!(f, "requests"),
+            Metric::TotalBytes => write!(f, "total_bytes"),
+            Metric::TotalTime => write!(f, "total_time"),
+            Metric::RequestTime => write!(f, "request_time"),
+            Metric::TimePercentile(p) => write!(f, "p{}_time", p),
+            Metric::ThirdPartyRequests => write!(f, "third_party_requests"),
+            Metric::ThirdPartyBytes => write!(f, "third_party_bytes"),
+            Metric::StatusClass(class) => write!(f, "status_{}xx", class),
+        }
+    }
+}
+
+fn lookup_percentile(map: &report::PercentileMap, p: f64) -> f64 {
+    map.0
+        .iter()
+        .find(|(q, _)| *q == p)
+        .map_or(0.0, |(_, value)| *value)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    Count,
+    Bytes,
+    Ms,
+}
+
+impl Unit {
+    pub fn format(self, value: f64) -> String {
+        match self {
+            Unit::Count => format!("{}", value),
+            Unit::Bytes => report::format_bytes(value as u64),
+            Unit::Ms => format!("{:.2} ms", value),
+        }
+    }
+
+    fn parse_limit(self, limit: &Limit) -> Result<f64, String> {
+        let value = match (self, limit) {
+            (_, Limit::Number(n)) => *n,
+            (Unit::Count, Limit::Text(s)) => s
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid count: {}", s))?,
+            (Unit::Bytes, Limit::Text(s)) => parse_bytes(s)?,
+            (Unit::Ms, Limit::Text(s)) => parse_duration_ms(s)?,
+        };
+        if value.is_finite() && value >= 0.0 {
+            Ok(value)
+        } else {
+            Err(format!("limit must be a non-negative number: {}", value))
+        }
+    }
+}
+
+/// Parses a size such as `512`, `300KB`, `2 MB` or `1.5MiB` into bytes (1 KB = 1024 B, as in
+/// the report output).
+pub fn parse_bytes(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let scale = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" | "kib" => 1024.0,
+        "mb" | "mib" => 1024.0 * 1024.0,
+        "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("invalid size (expected e.g. 300KB or 2MB): {}", s)),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(value * scale),
+        _ => Err(format!("invalid size (expected e.g. 300KB or 2MB): {}", s)),
+    }
+}
+
+/// A budget limit as written in the file: a plain number or a string with a unit.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Limit {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    name: Option<String>,
+    metric: String,
+    per: Option<GroupBy>,
+    max: Limit,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BudgetFile {
+    #[serde(default)]
+    first_party: Vec<String>,
+    #[serde(default, rename = "rule", alias = "rules")]
+    rules: Vec<RuleSpec>,
+}
+
+/// A single upper bound: `metric` (per group of `per`, if set) must be at most `max`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    pub metric: Metric,
+    pub per: Option<GroupBy>,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    /// Domains counted as first-party by the third-party metrics.
+    pub first_party: Vec<String>,
+    pub rules: Vec<Rule>,
+}
+
+/// Loads a budget file; `.toml` files are read as TOML, anything else as JSON.
+pub fn load(path: &Path) -> Result<Budget> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read budget file: {}", path.display()))?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let file: BudgetFile = if is_toml {
+        toml::from_str(&text).map_err(anyhow::Error::from)
+    } else {
+        serde_json::from_str(&text).map_err(anyhow::Error::from)
+    }
+    .with_context(|| format!("failed to parse budget file: {}", path.display()))?;
+    Budget::from_file(file)
+        .with_context(|| format!("failed to parse budget file: {}", path.display()))
+}
+
+impl Budget {
+    fn from_file(file: BudgetFile) -> Result<Self> {
+        if file.rules.is_empty() {
+            bail!("budget has no rules");
+        }
+        let mut rules = Vec::new();
+        for (index, spec) in file.rules.into_iter().enumerate() {
+            let rule_error = |message: String| anyhow!("rule {}: {}", index + 1, message);
+            let metric = Metric::parse(&spec.metric).map_err(|err| rule_error(err.to_string()))?;
+            if spec.per.is_some() && !metric.supports_groups() {
+                return Err(rule_error(format!(
+                    "metric {} cannot be evaluated per group",
+                    metric
+                )));
+            }
+            if metric.is_third_party() && file.first_party.is_empty() {
+                return Err(rule_error(format!(
+                    "metric {} needs first_party domains",
+                    metric
+                )));
+            }
+            let max = metric.unit().parse_limit(&spec.max).map_err(rule_error)?;
+            let name = spec.name.unwrap_or_else(|| {
+                let per = spec
+                    .per
+                    .map(|g| format!(" per {}", g.as_str()))
+                    .unwrap_or_default();
+                format!("{}{} <= {}", metric, per, metric.unit().format(max))
+            });
+            rules.push(Rule {
+                name,
+                metric,
+                per: spec.per,
+                max,
+            });
+        }
+        Ok(Self {
+            first_party: file.first_party,
+            rules,
+        })
+    }
+
+    /// Options for the reports the rules need: one per `per` dimension, or a single
+    /// ungrouped report.
+    pub fn report_options(&self) -> Vec<ReportOptions> {
+        let mut percentiles: Vec<f64> = Vec::new();
+        let mut dimensions: Vec<GroupBy> = Vec::new();
+        for rule in &self.rules {
+            if let Metric::TimePercentile(p) = rule.metric {
+                if !percentiles.contains(&p) {
+                    percentiles.push(p);
+                }
+            }
+            if let Some(per) = rule.per {
+                if !dimensions.contains(&per) {
+                    dimensions.push(per);
+                }
+            }
+        }
+
+        let base = ReportOptions {
+            top: 0,
+            all_groups: true,
+            percentiles,
+            first_party: self.first_party.clone(),
+            ..Default::default()
+        };
+        if dimensions.is_empty() {
+            return vec![base];
+        }
+        dimensions
+            .into_iter()
+            .map(|group_by| ReportOptions {
+                group_by: Some(GroupSpec::from(group_by)),
+                ..base.clone()
+            })
+            .collect()
+    }
+
+    /// Evaluates every rule against reports built with [`Budget::report_options`].
+    pub fn evaluate(&self, reports: &[Report]) -> BudgetResult {
+        let rules: Vec<RuleResult> = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let measurements = match rule.per {
+                    None => {
+                        let actual = reports.first().map_or(0.0, |r| rule.metric.measure(r));
+                        vec![Measurement::new(None, actual, rule.max)]
+                    }
+                    Some(per) => reports
+                        .iter()
+                        .find(|r| r.group_by == Some(GroupSpec::from(per)))
+                        .map(|r| {
+                            r.top_groups
+                                .iter()
+                                .map(|group| {
+                                    let actual = rule.metric.measure_group(group);
+                                    Measurement::new(Some(group.key.clone()), actual, rule.max)
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                };
+                RuleResult {
+                    name: rule.name.clone(),
+                    metric: rule.metric.to_string(),
+                    per: rule.per,
+                    unit: rule.metric.unit(),
+                    max: rule.max,
+                    passed: measurements.iter().all(|m| m.passed),
+                    measurements,
+                }
+            })
+            .collect();
+
+        BudgetResult {
+            sources: reports
+                .first()
+                .map(|r| r.sources.clone())
+                .unwrap_or_default(),
+            passed: rules.iter().all(|r| r.passed),
+            rules,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BudgetResult {
+    pub sources: Vec<String>,
+    pub passed: bool,
+    pub rules: Vec<RuleResult>,
+}
+
+impl BudgetResult {
+    pub fn failed_rules(&self) -> usize {
+        self.rules.iter().filter(|r| !r.passed).count()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleResult {
+    pub name: String,
+    pub metric: String,
+    pub per: Option<GroupBy>,
+    pub unit: Unit,
+    pub max: f64,
+    pub passed: bool,
+    /// One measurement for whole-report rules, one per group for `per` rules.
+    pub measurements: Vec<Measurement>,
+}
+
+impl RuleResult {
+    /// The measurement furthest over (or closest to) the limit.
+    pub fn worst(&self) -> Option<&Measurement> {
+        self.measurements
+            .iter()
+            .max_by(|a, b| a.actual.total_cmp(&b.actual))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Measurement {
+    /// Group key for `per` rules.
+    pub group: Option<String>,
+    pub actual: f64,
+    pub passed: bool,
+}
+
+impl Measurement {
+    fn new(group: Option<String>, actual: f64, max: f64) -> Self {
+        Self {
+            group,
+            actual,
+            passed: actual <= max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::{HarEntry, HarRequest, HarResponse};
+    use crate::report::build_report;
+
+    fn parse_toml(text: &str) -> Result<Budget> {
+        Budget::from_file(toml::from_str(text)?)
+    }
+
+    fn entry(url: &str, status: i64, time: f64, body: i64) -> HarEntry {
+        HarEntry {
+            time,
+            request: HarRequest {
+                url: url.to_string(),
+                ..Default::default()
+            },
+            response: HarResponse {
+                status,
+                body_size: Some(body),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_metrics_and_limits() {
+        assert_eq!(
+            Metric::parse("p99.9_time").ok(),
+            Some(Metric::TimePercentile(99.9))
+        );
+        assert_eq!(
+            Metric::parse("status_5xx").ok(),
+            Some(Metric::StatusClass(5))
+        );
+        assert!(Metric::parse("p0_time").is_err());
+        assert!(Metric::parse("status_9xx").is_err());
+        assert!(Metric::parse("latency").is_err());
+
+        assert_eq!(parse_bytes("2MB"), Ok(2.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_bytes("300 KiB"), Ok(300.0 * 1024.0));
+        assert_eq!(parse_bytes("512"), Ok(512.0));
+        assert!(parse_bytes("2 parsecs").is_err());
+
+        let budget = parse_toml(
+            r#"
+            first_party = ["example.com"]
+
+            [[rule]]
+            metric = "total_bytes"
+            max = "2 MB"
+
+            [[rule]]
+            metric = "p95_time"
+            per = "host"
+            max = "0.5s"
+
+            [[rule]]
+            name = "no 5xx"
+            metric = "status_5xx"
+            max = 0
+            "#,
+        )
+        .expect("valid budget");
+        assert_eq!(budget.rules.len(), 3);
+        assert_eq!(budget.rules[0].name, "total_bytes <= 2.00 MB");
+        assert_eq!(budget.rules[1].name, "p95_time per host <= 500.00 ms");
+        assert_eq!(budget.rules[1].max, 500.0);
+        assert_eq!(budget.rules[2].name, "no 5xx");
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let cases = [
+            (
+                "metric = \"status_5xx\"\nper = \"host\"\nmax = 0",
+                "per group",
+            ),
+            ("metric = \"third_party_requests\"\nmax = 50", "first_party"),
+            (
+                "metric = \"request_time\"\nmax = \"soon\"",
+                "invalid duration",
+            ),
+            ("metric = \"latency\"\nmax = 1", "unknown metric"),
+        ];
+        for (rule, message) in cases {
+            let err = parse_toml(&format!("[[rule]]\n{}", rule)).expect_err(rule);
+            assert!(err.to_string().contains(message), "{}: {}", rule, err);
+        }
+        assert!(parse_toml("").is_err());
+        assert!(parse_toml("[[rule]]\nmetric = \"requests\"\nmax = 1\nmin = 0").is_err());
+    }
+
+    #[test]
+    fn evaluates_rules_against_reports() {
+        let entries = vec![
+            entry("https://www.example.com/", 200, 100.0, 1000),
+            entry("https://api.example.com/a", 200, 300.0, 100),
+            entry("https://api.example.com/b", 503, 900.0, 100),
+            entry("https://cdn.other.net/x.js", 200, 50.0, 4000),
+        ];
+        let budget = parse_toml(
+            r#"
+            first_party = ["example.com"]
+            rules = [
+                { metric = "requests", max = 4 },
+                { metric = "request_time", max = "1s" },
+                { metric = "p95_time", per = "host", max = 500 },
+                { metric = "third_party_bytes", max = "2KB" },
+                { metric = "status_5xx", max = 0 },
+            ]
+            "#,
+        )
+        .expect("valid budget");
+
+        let reports: Vec<Report> = budget
+            .report_options()
+            .iter()
+            .map(|opts| build_report(&entries, opts))
+            .collect();
+        assert_eq!(reports.len(), 1);
+        let result = budget.evaluate(&reports);
+
+        let passed: Vec<bool> = result.rules.iter().map(|r| r.passed).collect();
+        assert_eq!(passed, vec![true, true, false, false, false]);
+        assert!(!result.passed);
+        assert_eq!(result.failed_rules(), 3);
+
+        let per_host = &result.rules[2];
+        assert_eq!(per_host.measurements.len(), 3);
+        let worst = per_host.worst().expect("measurements");
+        assert_eq!(worst.group.as_deref(), Some("api.example.com"));
+        assert_eq!(worst.actual, 900.0);
+        assert!(per_host
+            .measurements
+            .iter()
+            .filter(|m| m.group.as_deref() != Some("api.example.com"))
+            .all(|m| m.passed));
+
+        assert_eq!(result.rules[3].measurements[0].actual, 4000.0);
+        assert_eq!(result.rules[4].measurements[0].actual, 1.0);
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::har::HarEntry;
+use crate::report::{percentile, GroupBy, PercentileMethod, ReportBuilder, ReportOptions};
+use crate::stats::{self, Magnitude, Rng};
+
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    /// Dimensions compared independently, e.g. endpoint and host.
+    pub dimensions: Vec<GroupBy>,
+    /// False discovery rate for the Benjamini-Hochberg adjusted Mann-Whitney p-values.
+    pub alpha: f64,
+    /// Confidence level of the bootstrap interval.
+    pub confidence: f64,
+    pub resamples: usize,
+    pub seed: u64,
+    /// Grouping and filtering options applied to each run; the group and percentile
+    /// settings are replaced with those of the comparison.
+    pub grouping: ReportOptions,
+}
+
+/// Request times of one side, reduced to one value per run and group.
+///
+/// Requests within a run share its caching, connection reuse and server load, so they are not
+/// independent samples. Each run (HAR file) instead contributes the median time of each group it
+/// requested, and the tests treat runs as the unit of replication. Runs are grouped by a
+/// [`ReportBuilder`] per dimension, so keys and filters match those of `report`.
+#[derive(Debug)]
+pub struct Samples {
+    pub runs: Vec<String>,
+    pub entries: usize,
+    /// Per-run medians of every run already finished.
+    groups: HashMap<(GroupBy, String), Vec<f64>>,
+    /// Each dimension with the options grouping a run by it.
+    dimensions: Vec<(GroupBy, ReportOptions)>,
+    /// Groups of the run being read, one builder per dimension.
+    current: Vec<ReportBuilder>,
+}
+
+impl Samples {
+    pub fn new(opts: &CompareOptions) -> Self {
+        let dimensions: Vec<(GroupBy, ReportOptions)> = opts
+            .dimensions
+            .iter()
+            .map(|dimension| (*dimension, run_options(*dimension, opts)))
+            .collect();
+        Self {
+            runs: Vec::new(),
+            entries: 0,
+            groups: HashMap::new(),
+            current: dimensions
+                .iter()
+                .map(|(_, options)| ReportBuilder::new(options.clone()))
+                .collect(),
+            dimensions,
+        }
+    }
+
+    /// Starts the run `name`; entries pushed from now on belong to it.
+    pub fn start_run(&mut self, name: &str) {
+        self.finish_run();
+        self.runs.push(name.to_string());
+        for builder in &mut self.current {
+            builder.set_source(name);
+        }
+    }
+
+    pub fn push(&mut self, entry: &HarEntry) {
+        self.entries += 1;
+        for builder in &mut self.current {
+            builder.push(entry);
+        }
+    }
+
+    fn finish_run(&mut self) {
+        for ((dimension, options), builder) in self.dimensions.iter().zip(&mut self.current) {
+            let report = std::mem::replace(builder, ReportBuilder::new(options.clone())).finish();
+            for group in report.top_groups {
+                self.groups
+                    .entry((*dimension, group.key))
+                    .or_default()
+                    .push(group.median_time_ms);
+            }
+        }
+    }
+}
+
+/// Report options grouping a single run by `dimension`, keeping every group.
+fn run_options(dimension: GroupBy, opts: &CompareOptions) -> ReportOptions {
+    ReportOptions {
+        top: 0,
+        group_by: Some(dimension.into()),
+        all_groups: true,
+        sort_by: None,
+        percentiles: Vec::new(),
+        percentile_method: PercentileMethod::Linear,
+        waterfall: 0,
+        ..opts.grouping.clone()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub runs: Vec<String>,
+    pub entries: usize,
+}
+
+/// Summary of a group's per-run medians on one side.
+#[derive(Debug, Serialize)]
+pub struct SampleSummary {
+    /// Runs that requested the group.
+    pub runs: usize,
+    /// Median of the per-run medians.
+    pub median_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl SampleSummary {
+    fn new(sorted: &[f64]) -> Self {
+        Self {
+            runs: sorted.len(),
+            median_ms: stats::median(sorted),
+            p95_ms: percentile(sorted, 0.95, PercentileMethod::NearestRank),
+        }
+    }
+}
+
+/// A statistically significant change in a group's request times.
+#[derive(Debug, Serialize)]
+pub struct GroupChange {
+    pub dimension: GroupBy,
+    pub key: String,
+    pub baseline: SampleSummary,
+    pub candidate: SampleSummary,
+    /// Candidate median minus baseline median.
+    pub median_shift_ms: f64,
+    /// Bootstrap confidence interval of `median_shift_ms`, resampling runs.
+    pub ci_low_ms: f64,
+    pub ci_high_ms: f64,
+    pub u: f64,
+    pub p_value: f64,
+    /// `p_value` after the Benjamini-Hochberg correction over every tested group.
+    pub adjusted_p_value: f64,
+    /// Cliff's delta; positive when the candidate is slower.
+    pub effect_size: f64,
+    pub magnitude: Magnitude,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub baseline: RunSummary,
+    pub candidate: RunSummary,
+    pub dimensions: Vec<GroupBy>,
+    pub alpha: f64,
+    pub confidence: f64,
+    pub resamples: usize,
+    pub seed: u64,
+    /// Groups requested in at least two runs on both sides.
+    pub tested_groups: usize,
+    /// Groups left out for lack of runs on one side.
+    pub untested_groups: usize,
+    /// Significantly slower groups, lowest adjusted p-value first.
+    pub regressions: Vec<GroupChange>,
+    /// Significantly faster groups, lowest adjusted p-value first.
+    pub improvements: Vec<GroupChange>,
+}
+
+/// Compares the per-run medians of each group with the Mann-Whitney U test, corrects the
+/// p-values of all tested groups with Benjamini-Hochberg, and keeps the changes whose adjusted
+/// p-value is below `alpha` and whose bootstrap interval of the median shift excludes zero.
+pub fn compare(mut baseline: Samples, mut candidate: Samples, opts: &CompareOptions) -> Comparison {
+    baseline.finish_run();
+    candidate.finish_run();
+    let mut keys: Vec<&(GroupBy, String)> = baseline
+        .groups
+        .keys()
+        .filter(|key| candidate.groups.contains_key(*key))
+        .collect();
+    // Dimension order as requested, then key, so bootstrap draws do not depend on hashing.
+    keys.sort_by(|a, b| {
+        let position = |g: &GroupBy| opts.dimensions.iter().position(|d| d == g);
+        position(&a.0)
+            .cmp(&position(&b.0))
+            .then_with(|| a.1.cmp(&b.1))
+    });
+
+    let mut tested = Vec::new();
+    for key in keys {
+        let mut before = baseline.groups[key].clone();
+        let mut after = candidate.groups[key].clone();
+        if before.len() < 2 || after.len() < 2 {
+            continue;
+        }
+        before.sort_by(f64::total_cmp);
+        after.sort_by(f64::total_cmp);
+        let test = stats::mann_whitney_u(&before, &after);
+        tested.push((key, before, after, test));
+    }
+    let p_values: Vec<f64> = tested.iter().map(|(.., test)| test.p_value).collect();
+    let adjusted = stats::benjamini_hochberg(&p_values);
+
+    let mut rng = Rng::new(opts.seed);
+    let mut regressions = Vec::new();
+    let mut improvements = Vec::new();
+    for ((key, before, after, test), adjusted_p_value) in tested.iter().zip(adjusted) {
+        if adjusted_p_value >= opts.alpha {
+            continue;
+        }
+        let (ci_low_ms, ci_high_ms) =
+            stats::bootstrap_median_shift(before, after, opts.resamples, opts.confidence, &mut rng);
+        if ci_low_ms <= 0.0 && ci_high_ms >= 0.0 {
+            continue;
+        }
+
+        let effect_size = stats::cliffs_delta(test.u, before.len(), after.len());
+        let change = GroupChange {
+            dimension: key.0,
+            key: key.1.clone(),
+            median_shift_ms: stats::median(after) - stats::median(before),
+            baseline: SampleSummary::new(before),
+            candidate: SampleSummary::new(after),
+            ci_low_ms,
+            ci_high_ms,
+            u: test.u,
+            p_value: test.p_value,
+            adjusted_p_value,
+            effect_size,
+            magnitude: Magnitude::of(effect_size),
+        };
+        if effect_size > 0.0 {
+            regressions.push(change);
+        } else {
+            improvements.push(change);
+        }
+    }
+    let tested_groups = tested.len();
+
+    let total_groups = baseline
+        .groups
+        .keys()
+        .chain(
+            candidate
+                .groups
+                .keys()
+                .filter(|key| !baseline.groups.contains_key(*key)),
+        )
+        .count();
+    for changes in [&mut regressions, &mut improvements] {
+        changes.sort_by(|a, b| a.adjusted_p_value.total_cmp(&b.adjusted_p_value));
+    }
+
+    Comparison {
+        baseline: RunSummary {
+            runs: baseline.runs,
+            entries: baseline.entries,
+        },
+        candidate: RunSummary {
+            runs: candidate.runs,
+            entries: candidate.entries,
+        },
+        dimensions: opts.dimensions.clone(),
+        alpha: opts.alpha,
+        confidence: opts.confidence,
+        resamples: opts.resamples,
+        seed: opts.seed,
+        tested_groups,
+        untested_groups: total_groups - tested_groups,
+        regressions,
+        improvements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::HarRequest;
+
+    fn options() -> CompareOptions {
+        CompareOptions {
+            dimensions: vec![GroupBy::Endpoint, GroupBy::Host],
+            alpha: 0.05,
+            confidence: 0.95,
+            resamples: 1000,
+            seed: 1,
+            grouping: ReportOptions::default(),
+        }
+    }
+
+    /// One side with a run per element of `runs`.
+    fn samples(runs: &[Vec<(&str, f64)>], opts: &CompareOptions) -> Samples {
+        let mut samples = Samples::new(opts);
+        for (index, requests) in runs.iter().enumerate() {
+            samples.start_run(&format!("run-{}.har", index));
+            for (url, time) in requests {
+                let entry = HarEntry {
+                    time: *time,
+                    request: HarRequest {
+                        url: url.to_string(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                samples.push(&entry);
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn flags_only_significant_changes() {
+        let opts = options();
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        for i in 0..6 {
+            let jitter = i as f64;
+            before.push(vec![
+                ("https://api.test/items/1", 100.0 + jitter),
+                ("https://api.test/items/2", 104.0 + jitter),
+                ("https://cdn.test/logo.png", 50.0 + jitter),
+                ("https://www.test/slow", 300.0 + jitter),
+            ]);
+            after.push(vec![
+                ("https://api.test/items/3", 180.0 + jitter),
+                ("https://api.test/items/4", 184.0 + jitter),
+                ("https://cdn.test/logo.png", 55.0 - jitter),
+                ("https://www.test/slow", 200.0 + jitter),
+            ]);
+        }
+        before[0].push(("https://once.test/", 10.0));
+
+        let comparison = compare(samples(&before, &opts), samples(&after, &opts), &opts);
+        assert_eq!(comparison.baseline.runs.len(), 6);
+        assert_eq!(comparison.baseline.entries, 25);
+        // Endpoints /items/{id}, /logo.png, /slow and / plus four hosts.
+        assert_eq!(comparison.tested_groups, 6);
+        assert_eq!(comparison.untested_groups, 2);
+
+        let regressions: Vec<(GroupBy, &str)> = comparison
+            .regressions
+            .iter()
+            .map(|c| (c.dimension, c.key.as_str()))
+            .collect();
+        assert_eq!(
+            regressions,
+            vec![
+                (GroupBy::Endpoint, "/items/{id}"),
+                (GroupBy::Host, "api.test")
+            ]
+        );
+        let items = &comparison.regressions[0];
+        // Each run contributes the median of its two requests.
+        assert_eq!(items.baseline.runs, 6);
+        assert_eq!(items.baseline.median_ms, 104.5);
+        assert_eq!(items.median_shift_ms, 80.0);
+        assert!(items.ci_low_ms > 0.0 && items.ci_low_ms <= 80.0 && items.ci_high_ms >= 80.0);
+        assert_eq!(items.effect_size, 1.0);
+        assert_eq!(items.magnitude, Magnitude::Large);
+        // Four exact p-values of 2/924 among six tests.
+        assert!(items.p_value < 0.003);
+        assert!((items.adjusted_p_value - items.p_value * 6.0 / 4.0).abs() < 1e-12);
+
+        assert_eq!(comparison.improvements.len(), 2);
+        assert_eq!(comparison.improvements[0].effect_size, -1.0);
+        assert_eq!(comparison.improvements[0].median_shift_ms, -100.0);
+    }
+
+    #[test]
+    fn requests_within_one_run_are_not_replicates() {
+        let opts = options();
+        let run = |time: f64| -> Vec<(&str, f64)> {
+            (0..20)
+                .map(|i| ("https://api.test/items", time + i as f64))
+                .collect()
+        };
+
+        let single = compare(
+            samples(&[run(100.0)], &opts),
+            samples(&[run(200.0)], &opts),
+            &opts,
+        );
+        assert_eq!(single.tested_groups, 0);
+        assert_eq!(single.untested_groups, 2);
+
+        // Three runs per side cannot reach p < 0.1, however many requests each run holds.
+        let few = compare(
+            samples(&[run(100.0), run(101.0), run(102.0)], &opts),
+            samples(&[run(200.0), run(201.0), run(202.0)], &opts),
+            &opts,
+        );
+        assert_eq!(few.tested_groups, 2);
+        assert!(few.regressions.is_empty());
+    }
+
+    #[test]
+    fn runs_are_grouped_like_reports() {
+        let mut opts = options();
+        opts.grouping.filters.hosts = vec!["api.test".to_string()];
+        let runs: Vec<Vec<(&str, f64)>> = (0..3)
+            .map(|i| {
+                vec![
+                    ("https://api.test/items/1", 100.0 + i as f64),
+                    ("https://api.test/items/2", 110.0 + i as f64),
+                    ("https://cdn.test/logo.png", 50.0),
+                ]
+            })
+            .collect();
+
+        let comparison = compare(samples(&runs, &opts), samples(&runs, &opts), &opts);
+        assert_eq!(comparison.baseline.entries, 9);
+        // Only /items/{id} and api.test pass the host filter.
+        assert_eq!(comparison.tested_groups, 2);
+        assert_eq!(comparison.untested_groups, 0);
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ops::Sub;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Serialize;
+
+use crate::har::HarEntry;
+use crate::parsed_url::ParsedUrl;
+use crate::report::{entry_bytes, GroupRow, GroupSpec, Report};
+
+/// How requests are matched between the two captures.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchOptions {
+    /// Match on scheme, host and path only, ignoring the query string.
+    pub ignore_query: bool,
+}
+
+/// URL with lowercased scheme and host, default port dropped, decoded path, query pairs sorted
+/// and re-encoded, and no fragment. Unparseable URLs are used as-is.
+pub fn normalize_url(url: &str, opts: MatchOptions) -> String {
+    let Ok(parsed) = ParsedUrl::parse(url) else {
+        return url.trim().to_string();
+    };
+    let mut out = format!("{}:", parsed.scheme);
+    if let Some(host) = &parsed.host {
+        out.push_str("//");
+        out.push_str(host);
+        if let Some(port) = parsed.port {
+            out.push_str(&format!(":{}", port));
+        }
+    }
+    out.push_str(&parsed.path);
+    if !opts.ignore_query && !parsed.query.is_empty() {
+        let mut query = parsed.query;
+        query.sort();
+        let pairs: Vec<String> = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode_query(name), encode_query(value)))
+            .collect();
+        out.push('?');
+        out.push_str(&pairs.join("&"));
+    }
+    out
+}
+
+/// Escapes the separators of a decoded query name or value so distinct pairs stay distinct,
+/// e.g. `a=1%262` and `a=1&2`.
+fn encode_query(s: &str) -> String {
+    const SEPARATORS: &AsciiSet = &CONTROLS.add(b'%').add(b'&').add(b'=').add(b'#').add(b'+');
+    utf8_percent_encode(s, SEPARATORS).to_string()
+}
+
+/// A request reduced to what the diff needs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestRecord {
+    /// Uppercased method and normalized URL, e.g. `GET https://example.com/app.js`.
+    pub key: String,
+    pub url: String,
+    pub time_ms: f64,
+    pub bytes: u64,
+}
+
+impl RequestRecord {
+    pub fn new(entry: &HarEntry, opts: MatchOptions) -> Self {
+        let method = entry.request.method.trim().to_ascii_uppercase();
+        Self {
+            key: format!("{} {}", method, normalize_url(&entry.request.url, opts)),
+            url: entry.request.url.clone(),
+            time_ms: entry.time,
+            bytes: entry_bytes(entry),
+        }
+    }
+}
+
+/// A value in the baseline and the candidate, and how it changed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Delta<T> {
+    pub baseline: T,
+    pub candidate: T,
+    pub delta: T,
+}
+
+impl<T: Copy + Sub<Output = T>> Delta<T> {
+    pub fn new(baseline: T, candidate: T) -> Self {
+        Self {
+            baseline,
+            candidate,
+            delta: candidate - baseline,
+        }
+    }
+}
+
+/// One side of the comparison: its report (grouped if requested) and every request in order.
+#[derive(Debug)]
+pub struct Capture {
+    pub report: Report,
+    pub requests: Vec<RequestRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureSummary {
+    pub sources: Vec<String>,
+    pub entries: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Totals {
+    pub entries: Delta<i64>,
+    pub total_time_ms: Delta<f64>,
+    pub total_bytes: Delta<i64>,
+}
+
+/// A request found in both captures.
+#[derive(Debug, Serialize)]
+pub struct ChangedRequest {
+    pub key: String,
+    /// Candidate URL, before normalization.
+    pub url: String,
+    pub time_ms: Delta<f64>,
+    pub bytes: Delta<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupDelta {
+    pub key: String,
+    pub count: Delta<i64>,
+    pub total_time_ms: Delta<f64>,
+    pub avg_time_ms: Delta<f64>,
+    pub p95_time_ms: Delta<f64>,
+    pub total_bytes: Delta<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Diff {
+    pub baseline: CaptureSummary,
+    pub candidate: CaptureSummary,
+    pub totals: Totals,
+    pub matched_requests: usize,
+    pub added_requests: usize,
+    pub removed_requests: usize,
+    pub top_requested: usize,
+    /// Slowest requests only in the candidate.
+    pub added: Vec<RequestRecord>,
+    /// Slowest requests only in the baseline.
+    pub removed: Vec<RequestRecord>,
+    /// Matched requests with the largest absolute time change.
+    pub changed: Vec<ChangedRequest>,
+    pub group_by: Option<GroupSpec>,
+    /// Groups with the largest absolute total time change.
+    pub groups: Vec<GroupDelta>,
+}
+
+/// Compares two captures. Requests with the same key are paired in order of appearance, so the
+/// n-th `GET /poll` of the baseline is matched with the n-th of the candidate.
+pub fn diff(baseline: Capture, candidate: Capture, top: usize) -> Diff {
+    let mut pending: HashMap<&str, VecDeque<&RequestRecord>> = HashMap::new();
+    for request in &baseline.requests {
+        pending.entry(&request.key).or_default().push_back(request);
+    }
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for request in &candidate.requests {
+        match pending
+            .get_mut(request.key.as_str())
+            .and_then(VecDeque::pop_front)
+        {
+            Some(before) => changed.push(ChangedRequest {
+                key: request.key.clone(),
+                url: request.url.clone(),
+                time_ms: Delta::new(before.time_ms, request.time_ms),
+                bytes: Delta::new(before.bytes as i64, request.bytes as i64),
+            }),
+            None => added.push(request.clone()),
+        }
+    }
+    // Keep baseline order for unmatched requests.
+    let mut removed: Vec<RequestRecord> = Vec::new();
+    let mut unmatched: HashMap<&str, usize> = pending
+        .iter()
+        .map(|(key, left)| (*key, left.len()))
+        .collect();
+    for request in baseline.requests.iter().rev() {
+        if let Some(left) = unmatched.get_mut(request.key.as_str()) {
+            if *left > 0 {
+                *left -= 1;
+                removed.push(request.clone());
+            }
+        }
+    }
+    removed.reverse();
+
+    let matched_requests = changed.len();
+    let added_requests = added.len();
+    let removed_requests = removed.len();
+    let slowest_first = |a: &RequestRecord, b: &RequestRecord| b.time_ms.total_cmp(&a.time_ms);
+    added.sort_by(slowest_first);
+    added.truncate(top);
+    removed.sort_by(slowest_first);
+    removed.truncate(top);
+    changed.sort_by(|a, b| b.time_ms.delta.abs().total_cmp(&a.time_ms.delta.abs()));
+    changed.truncate(top);
+
+    let before = &baseline.report;
+    let after = &candidate.report;
+    Diff {
+        totals: Totals {
+            entries: Delta::new(before.entries as i64, after.entries as i64),
+            total_time_ms: Delta::new(before.total_time_ms, after.total_time_ms),
+            total_bytes: Delta::new(before.total_bytes as i64, after.total_bytes as i64),
+        },
+        groups: group_deltas(&before.top_groups, &after.top_groups, top),
+        group_by: after.group_by.clone(),
+        matched_requests,
+        added_requests,
+        removed_requests,
+        top_requested: top,
+        added,
+        removed,
+        changed,
+        baseline: CaptureSummary {
+            sources: baseline.report.sources,
+            entries: baseline.report.entries,
+        },
+        candidate: CaptureSummary {
+            sources: candidate.report.sources,
+            entries: candidate.report.entries,
+        },
+    }
+}
+
+/// Pairs groups by key; a group missing on one side counts as empty there.
+fn group_deltas(baseline: &[GroupRow], candidate: &[GroupRow], top: usize) -> Vec<GroupDelta> {
+    let mut pairs: BTreeMap<&str, (Option<&GroupRow>, Option<&GroupRow>)> = BTreeMap::new();
+    for group in baseline {
+        pairs.entry(&group.key).or_default().0 = Some(group);
+    }
+    for group in candidate {
+        pairs.entry(&group.key).or_default().1 = Some(group);
+    }
+
+    let mut deltas: Vec<GroupDelta> = pairs
+        .into_iter()
+        .map(|(key, (before, after))| {
+            let f =
+                |group: Option<&GroupRow>, value: fn(&GroupRow) -> f64| group.map_or(0.0, value);
+            let i = |group: Option<&GroupRow>, value: fn(&GroupRow) -> i64| group.map_or(0, value);
+            GroupDelta {
+                key: key.to_string(),
+                count: Delta::new(i(before, |g| g.count as i64), i(after, |g| g.count as i64)),
+                total_time_ms: Delta::new(
+                    f(before, |g| g.total_time_ms),
+                    f(after, |g| g.total_time_ms),
+                ),
+                avg_time_ms: Delta::new(f(before, |g| g.avg_time_ms), f(after, |g| g.avg_time_ms)),
+                p95_time_ms: Delta::new(f(before, |g| g.p95_time_ms), f(after, |g| g.p95_time_ms)),
+                total_bytes: Delta::new(
+                    i(before, |g| g.total_bytes as i64),
+                    i(after, |g| g.total_bytes as i64),
+                ),
+            }
+        })
+        .collect();
+    // Stable sort keeps ties in key order.
+    deltas.sort_by(|a, b| {
+        b.total_time_ms
+            .delta
+            .abs()
+            .total_cmp(&a.total_time_ms.delta.abs())
+    });
+    deltas.truncate(top);
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::{HarRequest, HarResponse};
+    use crate::report::{build_report, GroupBy, ReportOptions};
+
+    fn entry(method: &str, url: &str, time: f64, bytes: i64) -> HarEntry {
+        HarEntry {
+            time,
+            request: HarRequest {
+                method: method.to_string(),
+                url: url.to_string(),
+                ..Default::default()
+            },
+            response: HarResponse {
+                body_size: Some(bytes),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn capture(entries: &[HarEntry]) -> Capture {
+        let opts = ReportOptions {
+            group_by: Some(GroupSpec::from(GroupBy::Host)),
+            all_groups: true,
+            ..Default::default()
+        };
+        Capture {
+            report: build_report(entries, &opts),
+            requests: entries
+                .iter()
+                .map(|e| RequestRecord::new(e, MatchOptions::default()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn normalizes_urls_for_matching() {
+        let opts = MatchOptions::default();
+        assert_eq!(
+            normalize_url("HTTPS://Example.COM:443/a%20b?z=1&a=2#top", opts),
+            "https://example.com/a b?a=2&z=1"
+        );
+        assert_eq!(
+            normalize_url("http://example.com:8080", opts),
+            "http://example.com:8080/"
+        );
+        assert_eq!(
+            normalize_url(
+                "https://example.com/app.js?v=1",
+                MatchOptions { ignore_query: true }
+            ),
+            "https://example.com/app.js"
+        );
+        assert_eq!(normalize_url(" /relative ", opts), "/relative");
+        assert_ne!(
+            normalize_url("https://example.com/files/a%2Fb", opts),
+            normalize_url("https://example.com/files/a/b", opts)
+        );
+    }
+
+    #[test]
+    fn keeps_encoded_query_separators_distinct() {
+        let opts = MatchOptions::default();
+        let encoded = normalize_url("https://example.com/?a=1%262", opts);
+        assert_eq!(encoded, "https://example.com/?a=1%262");
+        assert_ne!(encoded, normalize_url("https://example.com/?a=1&2", opts));
+        assert_ne!(
+            normalize_url("https://example.com/?a=b%3Dc", opts),
+            normalize_url("https://example.com/?a%3Db=c", opts)
+        );
+        assert_eq!(
+            normalize_url("https://example.com/?q=a%2Bb&q=a+b", opts),
+            "https://example.com/?q=a b&q=a%2Bb"
+        );
+    }
+
+    #[test]
+    fn matches_requests_by_method_and_url_in_order() {
+        let baseline = capture(&[
+            entry("GET", "https://a.com/", 100.0, 1000),
+            entry("GET", "https://a.com/poll", 10.0, 10),
+            entry("GET", "https://a.com/poll", 20.0, 10),
+            entry("GET", "https://b.com/old.js", 50.0, 500),
+        ]);
+        let candidate = capture(&[
+            entry("get", "https://A.com:443/", 150.0, 900),
+            entry("GET", "https://a.com/poll", 15.0, 10),
+            entry("POST", "https://a.com/poll", 5.0, 0),
+            entry("GET", "https://c.com/new.js", 80.0, 700),
+        ]);
+
+        let diff = diff(baseline, candidate, 10);
+        assert_eq!(diff.matched_requests, 2);
+        assert_eq!(diff.added_requests, 2);
+        assert_eq!(diff.removed_requests, 2);
+
+        assert_eq!(diff.changed[0].key, "GET https://a.com/");
+        assert_eq!(diff.changed[0].time_ms, Delta::new(100.0, 150.0));
+        assert_eq!(diff.changed[0].bytes.delta, -100);
+        assert_eq!(diff.changed[1].time_ms.delta, 5.0);
+
+        let added: Vec<&str> = diff.added.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(
+            added,
+            vec!["GET https://c.com/new.js", "POST https://a.com/poll"]
+        );
+        let removed: Vec<(&str, f64)> = diff
+            .removed
+            .iter()
+            .map(|r| (r.key.as_str(), r.time_ms))
+            .collect();
+        assert_eq!(
+            removed,
+            vec![
+                ("GET https://b.com/old.js", 50.0),
+                ("GET https://a.com/poll", 20.0)
+            ]
+        );
+
+        assert_eq!(diff.totals.entries.delta, 0);
+        assert_eq!(diff.totals.total_time_ms, Delta::new(180.0, 250.0));
+        assert_eq!(diff.totals.total_bytes.delta, 90);
+
+        let groups: Vec<(&str, f64)> = diff
+            .groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.total_time_ms.delta))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![("c.com", 80.0), ("b.com", -50.0), ("a.com", 40.0)]
+        );
+        assert_eq!(diff.groups[1].count, Delta::new(1, 0));
+    }
+
+    #[test]
+    fn top_limits_every_list() {
+        let entries: Vec<HarEntry> = (0..5)
+            .map(|i| entry("GET", &format!("https://h{}.com/", i), i as f64, 1))
+            .collect();
+        let diff = diff(capture(&[]), capture(&entries), 2);
+        assert_eq!(diff.added_requests, 5);
+        assert_eq!(diff.added.len(), 2);
+        assert_eq!(diff.added[0].time_ms, 4.0);
+        assert_eq!(diff.groups.len(), 2);
+    }
+}
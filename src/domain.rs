@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+const PUBLIC_SUFFIX_LIST: &str = include_str!("public_suffix_list.dat");
+
+#[derive(Debug, Default)]
+struct SuffixRules {
+    exact: HashSet<String>,
+    wildcard: HashSet<String>,
+    exception: HashSet<String>,
+}
+
+/// Lowercase ASCII (punycode) form of `domain`, as `url` gives hosts; list rules and
+/// user-given domains may be written in Unicode, e.g. `公司.cn`.
+fn to_ascii(domain: &str) -> String {
+    idna::domain_to_ascii(domain).unwrap_or_else(|_| domain.to_ascii_lowercase())
+}
+
+fn rules() -> &'static SuffixRules {
+    static RULES: OnceLock<SuffixRules> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let mut rules = SuffixRules::default();
+        for line in PUBLIC_SUFFIX_LIST.lines() {
+            let rule = line.split_whitespace().next().unwrap_or_default();
+            if rule.is_empty() || rule.starts_with("//") {
+                continue;
+            }
+
+            if let Some(rest) = rule.strip_prefix('!') {
+                rules.exception.insert(to_ascii(rest));
+            } else if let Some(rest) = rule.strip_prefix("*.") {
+                rules.wildcard.insert(to_ascii(rest));
+            } else {
+                rules.exact.insert(to_ascii(rule));
+            }
+        }
+        rules
+    })
+}
+
+fn is_ip_literal(host: &str) -> bool {
+    host.starts_with('[') || host.parse::<std::net::Ipv4Addr>().is_ok()
+}
+
+/// Number of trailing labels of `labels` that form its public suffix.
+fn public_suffix_len(labels: &[&str]) -> usize {
+    let rules = rules();
+    for start in 0..labels.len() {
+        let candidate = labels[start..].join(".");
+        let len = labels.len() - start;
+
+        if rules.exception.contains(candidate.as_str()) {
+            return len - 1;
+        }
+        if rules.exact.contains(candidate.as_str()) {
+            return len;
+        }
+        if len > 1 {
+            let parent = labels[start + 1..].join(".");
+            if rules.wildcard.contains(parent.as_str()) {
+                return len;
+            }
+        }
+    }
+
+    // Implicit "*" rule: an unlisted TLD is itself a public suffix.
+    1
+}
+
+/// Registrable domain (eTLD+1) of `host`, e.g. `a.cdn.example.co.uk` -> `example.co.uk`.
+///
+/// Returns `None` for IP literals and for hosts that are themselves a public suffix.
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = to_ascii(host.trim_end_matches('.'));
+    if host.is_empty() || is_ip_literal(&host) {
+        return None;
+    }
+
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.iter().any(|l| l.is_empty()) {
+        return None;
+    }
+
+    let suffix_len = public_suffix_len(&labels);
+    if labels.len() <= suffix_len {
+        return None;
+    }
+    Some(labels[labels.len() - suffix_len - 1..].join("."))
+}
+
+/// True when `host` equals one of `domains` or is a subdomain of it.
+pub fn matches_any(host: &str, domains: &[String]) -> bool {
+    let host = to_ascii(host.trim_end_matches('.'));
+    domains.iter().any(|domain| {
+        let domain = to_ascii(domain.trim_matches('.'));
+        !domain.is_empty()
+            && (host == domain
+                || host
+                    .strip_suffix(&domain)
+                    .is_some_and(|prefix| prefix.ends_with('.')))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registrable_domain_uses_listed_and_implicit_suffixes() {
+        assert_eq!(
+            registrable_domain("a.cdn.example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            registrable_domain("www.shop.co.uk").as_deref(),
+            Some("shop.co.uk")
+        );
+        assert_eq!(
+            registrable_domain("d111.cloudfront.net").as_deref(),
+            Some("d111.cloudfront.net")
+        );
+        assert_eq!(
+            registrable_domain("static.example.unlisted").as_deref(),
+            Some("example.unlisted")
+        );
+        assert_eq!(registrable_domain("co.uk"), None);
+        assert_eq!(registrable_domain("localhost"), None);
+    }
+
+    #[test]
+    fn registrable_domain_covers_country_second_level_suffixes() {
+        for (host, expected) in [
+            ("shop.example.co.il", "example.co.il"),
+            ("www.example.com.sg", "example.com.sg"),
+            ("cdn.example.co.id", "example.co.id"),
+            ("library.example.ac.nz", "example.ac.nz"),
+            ("example.com.es", "example.com.es"),
+        ] {
+            assert_eq!(
+                registrable_domain(host).as_deref(),
+                Some(expected),
+                "{}",
+                host
+            );
+        }
+        assert_eq!(registrable_domain("co.il"), None);
+    }
+
+    #[test]
+    fn registrable_domain_handles_wildcard_and_exception_rules() {
+        assert_eq!(
+            registrable_domain("shop.gov.ck").as_deref(),
+            Some("shop.gov.ck")
+        );
+        assert_eq!(registrable_domain("gov.ck"), None);
+        assert_eq!(registrable_domain("www.ck").as_deref(), Some("www.ck"));
+    }
+
+    #[test]
+    fn registrable_domain_matches_internationalized_suffixes() {
+        // `公司.cn` is listed in Unicode; URL hosts arrive as punycode.
+        assert_eq!(
+            registrable_domain("shop.example.xn--55qx5d.cn").as_deref(),
+            Some("example.xn--55qx5d.cn")
+        );
+        assert_eq!(
+            registrable_domain("shop.example.公司.cn").as_deref(),
+            Some("example.xn--55qx5d.cn")
+        );
+        assert_eq!(registrable_domain("xn--55qx5d.cn"), None);
+    }
+
+    #[test]
+    fn registrable_domain_skips_ip_literals() {
+        assert_eq!(registrable_domain("192.168.1.10"), None);
+        assert_eq!(registrable_domain("[::1]"), None);
+    }
+
+    #[test]
+    fn matches_any_accepts_exact_and_subdomains_only() {
+        let first_party = vec!["example.com".to_string(), ".Example.NET".to_string()];
+        assert!(matches_any("example.com", &first_party));
+        assert!(matches_any("api.example.com", &first_party));
+        assert!(matches_any("cdn.example.net", &first_party));
+        assert!(!matches_any("notexample.com", &first_party));
+        assert!(!matches_any("example.org", &first_party));
+
+        let first_party = vec!["bücher.example".to_string()];
+        assert!(matches_any("shop.xn--bcher-kva.example", &first_party));
+    }
+}
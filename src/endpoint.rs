@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::parsed_url::decode_segment;
+
+/// How request paths are turned into endpoint keys for `--group-by endpoint`.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointRules {
+    /// Prefix each endpoint with the request method (`GET /users/{id}`).
+    pub with_method: bool,
+    /// User-supplied templates, tried in order before automatic normalization.
+    pub templates: Vec<PathTemplate>,
+}
+
+impl EndpointRules {
+    /// Endpoint key of a request; `path` is percent-encoded, as in [`ParsedUrl::raw_path`].
+    ///
+    /// [`ParsedUrl::raw_path`]: crate::parsed_url::ParsedUrl::raw_path
+    pub fn endpoint(&self, method: &str, path: &str) -> String {
+        let template = self
+            .templates
+            .iter()
+            .find(|t| t.matches(path))
+            .map(|t| t.raw.clone())
+            .unwrap_or_else(|| normalize_path(path));
+
+        if self.with_method && !method.trim().is_empty() {
+            format!("{} {}", method.trim().to_ascii_uppercase(), template)
+        } else {
+            template
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplateSegment {
+    Literal(String),
+    Param,
+}
+
+/// A path such as `/users/{user}/orders/{order}`; `{...}` segments match any value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTemplate {
+    pub raw: String,
+    segments: Vec<TemplateSegment>,
+}
+
+impl PathTemplate {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        if !raw.starts_with('/') {
+            bail!("endpoint template must start with '/': {}", raw);
+        }
+
+        let segments = path_segments(raw)
+            .map(|segment| {
+                if segment.starts_with('{') && segment.ends_with('}') && segment.len() > 2 {
+                    TemplateSegment::Param
+                } else {
+                    TemplateSegment::Literal(segment.to_string())
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            raw: raw.to_string(),
+            segments,
+        })
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let mut segments = path_segments(path);
+        let matched = self.segments.iter().all(|expected| match segments.next() {
+            Some(actual) => match expected {
+                TemplateSegment::Literal(literal) => *literal == decode_segment(actual),
+                TemplateSegment::Param => !actual.is_empty(),
+            },
+            None => false,
+        });
+        matched && segments.next().is_none()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TemplateFile {
+    templates: Vec<String>,
+}
+
+/// Loads `{"templates": ["/users/{id}", ...]}` from a JSON file.
+pub fn load_templates(path: &Path) -> Result<Vec<PathTemplate>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read endpoint templates: {}", path.display()))?;
+    let file: TemplateFile = serde_json::from_slice(&bytes)
+        .with_context(|| format!("failed to parse endpoint templates: {}", path.display()))?;
+    file.templates
+        .iter()
+        .map(|t| PathTemplate::parse(t))
+        .collect()
+}
+
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.trim_matches('/').split('/').filter(|s| !s.is_empty())
+}
+
+fn is_uuid(segment: &str) -> bool {
+    let groups: Vec<&str> = segment.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(g, len)| g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_hex(segment: &str) -> bool {
+    segment.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Base64/base64url tokens: long, mixing letters and digits, and not a plain word.
+fn is_token(segment: &str) -> bool {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '-' | '_');
+    segment.len() >= 20
+        && segment.chars().all(allowed)
+        && segment.chars().any(|c| c.is_ascii_digit())
+        && segment.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+fn normalize_segment(segment: &str) -> &str {
+    if segment.chars().all(|c| c.is_ascii_digit()) {
+        "{id}"
+    } else if is_uuid(segment) {
+        "{uuid}"
+    } else if is_hex(segment) && matches!(segment.len(), 32 | 40 | 64) {
+        "{hash}"
+    } else if is_hex(segment) && segment.len() >= 16 {
+        "{hex}"
+    } else if is_token(segment) {
+        "{token}"
+    } else {
+        segment
+    }
+}
+
+/// Replaces ID-like path segments with placeholders: `/users/42` -> `/users/{id}`.
+///
+/// Segments are split on the encoded path and decoded afterwards, so `a%2Fb` stays one segment.
+pub fn normalize_path(path: &str) -> String {
+    let segments: Vec<String> = path_segments(path)
+        .map(|segment| decode_segment(normalize_segment(segment)))
+        .collect();
+    let mut normalized = format!("/{}", segments.join("/"));
+    if path.len() > 1 && path.ends_with('/') {
+        normalized.push('/');
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_path_replaces_id_like_segments() {
+        assert_eq!(normalize_path("/"), "/");
+        assert_eq!(
+            normalize_path("/users/12345/orders/9f3c2a1e-7b4d-4c8e-a1f2-0123456789ab"),
+            "/users/{id}/orders/{uuid}"
+        );
+        assert_eq!(
+            normalize_path("/assets/d41d8cd98f00b204e9800998ecf8427e/app.js"),
+            "/assets/{hash}/app.js"
+        );
+        assert_eq!(
+            normalize_path("/objects/deadbeefcafe0042ab"),
+            "/objects/{hex}"
+        );
+        assert_eq!(
+            normalize_path("/share/aGVsbG8td29ybGQtMTIzNDU2Nzg5"),
+            "/share/{token}"
+        );
+        assert_eq!(normalize_path("/v1/events/"), "/v1/events/");
+        assert_eq!(normalize_path("/api/v2/search"), "/api/v2/search");
+    }
+
+    #[test]
+    fn encoded_slashes_do_not_split_segments() {
+        assert_eq!(normalize_path("/files/a%2Fb"), "/files/a%2Fb");
+        assert_eq!(normalize_path("/files/42/caf%C3%A9"), "/files/{id}/café");
+
+        let rules = EndpointRules {
+            with_method: false,
+            templates: vec![PathTemplate::parse("/files/{name}").expect("valid")],
+        };
+        assert_eq!(rules.endpoint("GET", "/files/a%2Fb"), "/files/{name}");
+        assert_eq!(rules.endpoint("GET", "/files/a/b"), "/files/a/b");
+    }
+
+    #[test]
+    fn templates_take_priority_over_normalization() {
+        let rules = EndpointRules {
+            with_method: false,
+            templates: vec![PathTemplate::parse("/users/{user}/profile").expect("valid")],
+        };
+
+        assert_eq!(
+            rules.endpoint("GET", "/users/alice/profile"),
+            "/users/{user}/profile"
+        );
+        assert_eq!(
+            rules.endpoint("GET", "/users/42/settings"),
+            "/users/{id}/settings"
+        );
+        assert_eq!(
+            rules.endpoint("GET", "/users/alice/profile/x"),
+            "/users/alice/profile/x"
+        );
+    }
+
+    #[test]
+    fn endpoint_can_include_method() {
+        let rules = EndpointRules {
+            with_method: true,
+            templates: Vec::new(),
+        };
+        assert_eq!(rules.endpoint("delete", "/items/7"), "DELETE /items/{id}");
+    }
+
+    #[test]
+    fn template_must_be_absolute_path() {
+        let err = PathTemplate::parse("users/{id}").expect_err("relative template");
+        assert!(err.to_string().contains("must start with '/'"));
+    }
+}
@@ -0,0 +1,782 @@
+use std::fmt;
+
+use regex::Regex;
+use serde::{Serialize, Serializer};
+
+use crate::har::{HarEntry, HarNameValue};
+use crate::parsed_url::ParsedUrl;
+use crate::report::{entry_bytes, entry_ttfb_ms, mime_type};
+
+/// A parse error with the 1-based column of the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl ExprError {
+    /// Message followed by the expression with a caret under the offending column.
+    pub fn render(&self, source: &str) -> String {
+        format!(
+            "{} at column {}\n  {}\n  {}^",
+            self.message,
+            self.column,
+            source,
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+/// A value produced while evaluating an expression; missing fields evaluate to `Null`.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::String(s) => !s.is_empty(),
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self) -> Option<String> {
+        match self {
+            Value::Null => None,
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::String(s) => Some(s.clone()),
+        }
+    }
+}
+
+/// Entry fields an expression can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Time,
+    StartedDateTime,
+    Pageref,
+    ServerIp,
+    Connection,
+    RequestMethod,
+    RequestUrl,
+    RequestHttpVersion,
+    RequestHeadersSize,
+    RequestBodySize,
+    ResponseStatus,
+    ResponseStatusText,
+    ResponseHttpVersion,
+    ResponseRedirectUrl,
+    ResponseHeadersSize,
+    ResponseBodySize,
+    ContentSize,
+    ContentMimeType,
+    ContentText,
+    Timing(usize),
+    Host,
+    Scheme,
+    Path,
+    Mime,
+    Bytes,
+    Ttfb,
+}
+
+/// Field names accepted in expressions, with short aliases for the common ones.
+const FIELDS: &[(&str, Field)] = &[
+    ("time", Field::Time),
+    ("startedDateTime", Field::StartedDateTime),
+    ("pageref", Field::Pageref),
+    ("serverIPAddress", Field::ServerIp),
+    ("connection", Field::Connection),
+    ("request.method", Field::RequestMethod),
+    ("request.url", Field::RequestUrl),
+    ("request.httpVersion", Field::RequestHttpVersion),
+    ("request.headersSize", Field::RequestHeadersSize),
+    ("request.bodySize", Field::RequestBodySize),
+    ("response.status", Field::ResponseStatus),
+    ("response.statusText", Field::ResponseStatusText),
+    ("response.httpVersion", Field::ResponseHttpVersion),
+    ("response.redirectURL", Field::ResponseRedirectUrl),
+    ("response.headersSize", Field::ResponseHeadersSize),
+    ("response.bodySize", Field::ResponseBodySize),
+    ("response.content.size", Field::ContentSize),
+    ("response.content.mimeType", Field::ContentMimeType),
+    ("response.content.text", Field::ContentText),
+    ("timings.blocked", Field::Timing(0)),
+    ("timings.dns", Field::Timing(1)),
+    ("timings.connect", Field::Timing(2)),
+    ("timings.ssl", Field::Timing(3)),
+    ("timings.send", Field::Timing(4)),
+    ("timings.wait", Field::Timing(5)),
+    ("timings.receive", Field::Timing(6)),
+    ("method", Field::RequestMethod),
+    ("url", Field::RequestUrl),
+    ("status", Field::ResponseStatus),
+    ("host", Field::Host),
+    ("scheme", Field::Scheme),
+    ("path", Field::Path),
+    ("mime", Field::Mime),
+    ("bytes", Field::Bytes),
+    ("ttfb", Field::Ttfb),
+];
+
+fn optional_number(value: Option<i64>) -> Value {
+    value.map_or(Value::Null, |v| Value::Number(v as f64))
+}
+
+fn optional_string(value: Option<&str>) -> Value {
+    value.map_or(Value::Null, |v| Value::String(v.to_string()))
+}
+
+impl Field {
+    fn eval(self, entry: &HarEntry) -> Value {
+        let request = &entry.request;
+        let response = &entry.response;
+        let content = response.content.as_ref();
+        match self {
+            Field::Time => Value::Number(entry.time),
+            Field::StartedDateTime => Value::String(entry.started_date_time.clone()),
+            Field::Pageref => optional_string(entry.pageref.as_deref()),
+            Field::ServerIp => optional_string(entry.server_ip_address.as_deref()),
+            Field::Connection => optional_string(entry.connection.as_deref()),
+            Field::RequestMethod => Value::String(request.method.clone()),
+            Field::RequestUrl => Value::String(request.url.clone()),
+            Field::RequestHttpVersion => Value::String(request.http_version.clone()),
+            Field::RequestHeadersSize => optional_number(request.headers_size),
+            Field::RequestBodySize => optional_number(request.body_size),
+            Field::ResponseStatus => Value::Number(response.status as f64),
+            Field::ResponseStatusText => Value::String(response.status_text.clone()),
+            Field::ResponseHttpVersion => Value::String(response.http_version.clone()),
+            Field::ResponseRedirectUrl => Value::String(response.redirect_url.clone()),
+            Field::ResponseHeadersSize => optional_number(response.headers_size),
+            Field::ResponseBodySize => optional_number(response.body_size),
+            Field::ContentSize => optional_number(content.and_then(|c| c.size)),
+            Field::ContentMimeType => optional_string(content.map(|c| c.mime_type.as_str())),
+            Field::ContentText => optional_string(content.and_then(|c| c.text.as_deref())),
+            Field::Timing(index) => {
+                let t = &entry.timings;
+                let values = [
+                    t.blocked, t.dns, t.connect, t.ssl, t.send, t.wait, t.receive,
+                ];
+                Value::Number(values[index])
+            }
+            Field::Host => ParsedUrl::parse(&request.url)
+                .ok()
+                .and_then(|u| u.host)
+                .map_or(Value::Null, Value::String),
+            Field::Scheme => {
+                ParsedUrl::parse(&request.url).map_or(Value::Null, |u| Value::String(u.scheme))
+            }
+            Field::Path => {
+                ParsedUrl::parse(&request.url).map_or(Value::Null, |u| Value::String(u.path))
+            }
+            Field::Mime => Value::String(mime_type(entry)),
+            Field::Bytes => Value::Number(entry_bytes(entry) as f64),
+            Field::Ttfb => Value::Number(entry_ttfb_ms(entry)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    /// Response header value, case-insensitive by name.
+    Header,
+    RequestHeader,
+    /// Query string parameter value.
+    Query,
+}
+
+const FUNCTIONS: &[(&str, Function)] = &[
+    ("header", Function::Header),
+    ("request_header", Function::RequestHeader),
+    ("query", Function::Query),
+];
+
+fn lookup(pairs: &[HarNameValue], name: &str, ignore_case: bool) -> Value {
+    pairs
+        .iter()
+        .find(|pair| {
+            if ignore_case {
+                pair.name.eq_ignore_ascii_case(name)
+            } else {
+                pair.name == name
+            }
+        })
+        .map_or(Value::Null, |pair| Value::String(pair.value.clone()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Literal(Value),
+    Field(Field),
+    Call(Function, String),
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Compare(CompareOp, Box<Node>, Box<Node>),
+    Matches(Box<Node>, Regex, bool),
+}
+
+impl Node {
+    fn eval(&self, entry: &HarEntry) -> Value {
+        match self {
+            Node::Literal(value) => value.clone(),
+            Node::Field(field) => field.eval(entry),
+            Node::Call(Function::Header, name) => lookup(&entry.response.headers, name, true),
+            Node::Call(Function::RequestHeader, name) => lookup(&entry.request.headers, name, true),
+            Node::Call(Function::Query, name) => lookup(&entry.request.query_string, name, false),
+            Node::Not(inner) => Value::Bool(!inner.eval(entry).truthy()),
+            Node::And(a, b) => Value::Bool(a.eval(entry).truthy() && b.eval(entry).truthy()),
+            Node::Or(a, b) => Value::Bool(a.eval(entry).truthy() || b.eval(entry).truthy()),
+            Node::Compare(op, a, b) => Value::Bool(compare(*op, &a.eval(entry), &b.eval(entry))),
+            Node::Matches(inner, regex, negate) => {
+                let matched = inner
+                    .eval(entry)
+                    .as_text()
+                    .is_some_and(|text| regex.is_match(&text));
+                Value::Bool(matched != *negate)
+            }
+        }
+    }
+}
+
+/// Compares numerically when both sides are numbers (or numeric strings), otherwise as
+/// strings. Comparisons involving a missing value are false, except `!=`.
+fn compare(op: CompareOp, a: &Value, b: &Value) -> bool {
+    use std::cmp::Ordering;
+
+    let ordering = match (a, b) {
+        (Value::Null, Value::Null) => Some(Ordering::Equal),
+        (Value::Null, _) | (_, Value::Null) => None,
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        _ => match (a.as_number(), b.as_number()) {
+            (Some(x), Some(y)) => x.partial_cmp(&y),
+            _ => None,
+        },
+    };
+    match (op, ordering) {
+        (CompareOp::Ne, ordering) => ordering != Some(Ordering::Equal),
+        (_, None) => false,
+        (CompareOp::Eq, Some(o)) => o == Ordering::Equal,
+        (CompareOp::Lt, Some(o)) => o == Ordering::Less,
+        (CompareOp::Le, Some(o)) => o != Ordering::Greater,
+        (CompareOp::Gt, Some(o)) => o == Ordering::Greater,
+        (CompareOp::Ge, Some(o)) => o != Ordering::Less,
+    }
+}
+
+/// A parsed `--where` expression; serializes as its source text.
+#[derive(Debug, Clone)]
+pub struct Expr {
+    source: String,
+    root: Node,
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, index: 0 };
+        let root = parser.or()?;
+        let token = parser.peek();
+        if token.kind != TokenKind::End {
+            return Err(ExprError {
+                message: format!("unexpected {}", token.kind.describe()),
+                column: token.column,
+            });
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    pub fn matches(&self, entry: &HarEntry) -> bool {
+        self.root.eval(entry).truthy()
+    }
+}
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+/// Parses a `--where` value, pointing at the offending column on error.
+pub fn parse_where(s: &str) -> Result<Expr, String> {
+    Expr::parse(s).map_err(|err| err.render(s))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number(f64),
+    String(String),
+    And,
+    Or,
+    Not,
+    Compare(CompareOp),
+    Match,
+    NotMatch,
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+    End,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Ident(name) => format!("'{}'", name),
+            TokenKind::Number(n) => format!("number {}", n),
+            TokenKind::String(s) => format!("string '{}'", s),
+            TokenKind::And => "'and'".to_string(),
+            TokenKind::Or => "'or'".to_string(),
+            TokenKind::Not => "'not'".to_string(),
+            TokenKind::Compare(_) | TokenKind::Match | TokenKind::NotMatch => {
+                "operator".to_string()
+            }
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::Comma => "','".to_string(),
+            TokenKind::Dot => "'.'".to_string(),
+            TokenKind::End => "end of expression".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExprError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let two: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        let (kind, len) = match two.as_str() {
+            "==" => (TokenKind::Compare(CompareOp::Eq), 2),
+            "!=" => (TokenKind::Compare(CompareOp::Ne), 2),
+            "<=" => (TokenKind::Compare(CompareOp::Le), 2),
+            ">=" => (TokenKind::Compare(CompareOp::Ge), 2),
+            "!~" => (TokenKind::NotMatch, 2),
+            "&&" => (TokenKind::And, 2),
+            "||" => (TokenKind::Or, 2),
+            _ => match c {
+                '<' => (TokenKind::Compare(CompareOp::Lt), 1),
+                '>' => (TokenKind::Compare(CompareOp::Gt), 1),
+                '~' => (TokenKind::Match, 1),
+                '!' => (TokenKind::Not, 1),
+                '(' => (TokenKind::LParen, 1),
+                ')' => (TokenKind::RParen, 1),
+                ',' => (TokenKind::Comma, 1),
+                '.' if !chars.get(i + 1).is_some_and(char::is_ascii_digit) => (TokenKind::Dot, 1),
+                '=' => {
+                    return Err(ExprError {
+                        message: "unexpected '=', use '==' to compare".to_string(),
+                        column,
+                    })
+                }
+                '\'' | '"' => {
+                    let (text, len) = string_literal(&chars[i..], column)?;
+                    (TokenKind::String(text), len)
+                }
+                c if c.is_ascii_digit() || c == '.' || c == '-' => {
+                    let len = 1 + chars[i + 1..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit() || **c == '.')
+                        .count();
+                    let text: String = chars[i..i + len].iter().collect();
+                    let number = text.parse().map_err(|_| ExprError {
+                        message: format!("invalid number '{}'", text),
+                        column,
+                    })?;
+                    (TokenKind::Number(number), len)
+                }
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    let len = chars[i..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                        .count();
+                    let word: String = chars[i..i + len].iter().collect();
+                    let kind = match word.to_ascii_lowercase().as_str() {
+                        "and" => TokenKind::And,
+                        "or" => TokenKind::Or,
+                        "not" => TokenKind::Not,
+                        _ => TokenKind::Ident(word),
+                    };
+                    (kind, len)
+                }
+                c => {
+                    return Err(ExprError {
+                        message: format!("unexpected character '{}'", c),
+                        column,
+                    })
+                }
+            },
+        };
+        tokens.push(Token { kind, column });
+        i += len;
+    }
+    tokens.push(Token {
+        kind: TokenKind::End,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+/// Reads a quoted string starting at `chars[0]`; returns its text and length in chars.
+fn string_literal(chars: &[char], column: usize) -> Result<(String, usize), ExprError> {
+    let quote = chars[0];
+    let mut text = String::new();
+    let mut i = 1;
+    while let Some(&c) = chars.get(i) {
+        match c {
+            c if c == quote => return Ok((text, i + 1)),
+            '\\' => {
+                match chars.get(i + 1) {
+                    Some(&escaped) => text.push(escaped),
+                    None => break,
+                }
+                i += 2;
+            }
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(ExprError {
+        message: "unterminated string".to_string(),
+        column,
+    })
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.index].clone();
+        if token.kind != TokenKind::End {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, ExprError> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(ExprError {
+                message: format!(
+                    "expected {}, found {}",
+                    kind.describe(),
+                    token.kind.describe()
+                ),
+                column: token.column,
+            })
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.and()?;
+        while self.peek().kind == TokenKind::Or {
+            self.next();
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.not()?;
+        while self.peek().kind == TokenKind::And {
+            self.next();
+            node = Node::And(Box::new(node), Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self) -> Result<Node, ExprError> {
+        if self.peek().kind == TokenKind::Not {
+            self.next();
+            return Ok(Node::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, ExprError> {
+        let left = self.operand()?;
+        match self.peek().kind.clone() {
+            TokenKind::Compare(op) => {
+                self.next();
+                let right = self.operand()?;
+                Ok(Node::Compare(op, Box::new(left), Box::new(right)))
+            }
+            kind @ (TokenKind::Match | TokenKind::NotMatch) => {
+                self.next();
+                let token = self.next();
+                let TokenKind::String(pattern) = token.kind else {
+                    return Err(ExprError {
+                        message: format!(
+                            "expected a quoted regex after '~', found {}",
+                            token.kind.describe()
+                        ),
+                        column: token.column,
+                    });
+                };
+                let regex = Regex::new(&pattern).map_err(|err| ExprError {
+                    message: format!("invalid regex: {}", err),
+                    column: token.column,
+                })?;
+                Ok(Node::Matches(
+                    Box::new(left),
+                    regex,
+                    kind == TokenKind::NotMatch,
+                ))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Node, ExprError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Number(n) => Ok(Node::Literal(Value::Number(n))),
+            TokenKind::String(s) => Ok(Node::Literal(Value::String(s))),
+            TokenKind::LParen => {
+                let node = self.or()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(node)
+            }
+            TokenKind::Ident(name) => self.identifier(name, token.column),
+            other => Err(ExprError {
+                message: format!("expected a value, found {}", other.describe()),
+                column: token.column,
+            }),
+        }
+    }
+
+    fn identifier(&mut self, name: String, column: usize) -> Result<Node, ExprError> {
+        match name.to_ascii_lowercase().as_str() {
+            "true" => return Ok(Node::Literal(Value::Bool(true))),
+            "false" => return Ok(Node::Literal(Value::Bool(false))),
+            "null" => return Ok(Node::Literal(Value::Null)),
+            _ => {}
+        }
+
+        if self.peek().kind == TokenKind::LParen {
+            let function = FUNCTIONS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, f)| *f)
+                .ok_or_else(|| ExprError {
+                    message: format!(
+                        "unknown function '{}' (expected one of: {})",
+                        name,
+                        FUNCTIONS
+                            .iter()
+                            .map(|(n, _)| *n)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    column,
+                })?;
+            self.next();
+            let arg = self.next();
+            let TokenKind::String(arg_value) = arg.kind else {
+                return Err(ExprError {
+                    message: format!("{}() takes a quoted name", name),
+                    column: arg.column,
+                });
+            };
+            self.expect(TokenKind::RParen)?;
+            return Ok(Node::Call(function, arg_value));
+        }
+
+        let mut path = name;
+        while self.peek().kind == TokenKind::Dot {
+            self.next();
+            let token = self.next();
+            let TokenKind::Ident(part) = token.kind else {
+                return Err(ExprError {
+                    message: format!("expected a field name, found {}", token.kind.describe()),
+                    column: token.column,
+                });
+            };
+            path.push('.');
+            path.push_str(&part);
+        }
+        FIELDS
+            .iter()
+            .find(|(n, _)| *n == path)
+            .map(|(_, field)| Node::Field(*field))
+            .ok_or_else(|| ExprError {
+                message: format!("unknown field '{}'", path),
+                column,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::{HarRequest, HarResponse, HarTimings};
+
+    fn entry(url: &str, status: i64, time: f64) -> HarEntry {
+        HarEntry {
+            time,
+            request: HarRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                query_string: vec![HarNameValue {
+                    name: "v".to_string(),
+                    value: "7".to_string(),
+                    comment: None,
+                }],
+                ..Default::default()
+            },
+            response: HarResponse {
+                status,
+                headers: vec![HarNameValue {
+                    name: "Cache-Control".to_string(),
+                    value: "no-store".to_string(),
+                    comment: None,
+                }],
+                ..Default::default()
+            },
+            timings: HarTimings {
+                wait: 120.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn matches(expr: &str, entry: &HarEntry) -> bool {
+        Expr::parse(expr).expect("valid expression").matches(entry)
+    }
+
+    #[test]
+    fn evaluates_comparisons_and_boolean_logic() {
+        let slow_api = entry("https://api.example.com/users?v=7", 200, 900.0);
+        let error = entry("https://www.example.com/", 503, 50.0);
+        let fast_api = entry("https://api.example.com/health", 200, 20.0);
+        let expr = "status >= 500 or (host ~ 'api' and time > 800)";
+
+        assert!(matches(expr, &slow_api));
+        assert!(matches(expr, &error));
+        assert!(!matches(expr, &fast_api));
+
+        assert!(matches(
+            "request.method == 'GET' && timings.wait >= 120",
+            &slow_api
+        ));
+        assert!(matches("not response.status != 200", &slow_api));
+        assert!(matches("!(path ~ '^/health')", &slow_api));
+        assert!(matches("path !~ 'users'", &fast_api));
+        assert!(matches("status == '200'", &slow_api));
+        assert!(matches("timings.dns == -1 || false", &slow_api));
+        assert!(matches("timings.dns < 0", &slow_api));
+    }
+
+    #[test]
+    fn evaluates_functions_and_missing_values() {
+        let e = entry("https://api.example.com/users?v=7", 200, 900.0);
+        assert!(matches("header(\"cache-control\") == 'no-store'", &e));
+        assert!(matches("header('x-missing') == null", &e));
+        assert!(!matches("header('x-missing') == ''", &e));
+        assert!(matches("header('x-missing') != 'a'", &e));
+        assert!(!matches("request_header('accept')", &e));
+        assert!(matches("query('v') > 5", &e));
+        assert!(!matches("pageref ~ '.'", &e));
+        assert!(matches("response.content.text == null", &e));
+    }
+
+    #[test]
+    fn reports_errors_at_the_offending_column() {
+        let cases = [
+            ("status >= 500 )", "unexpected ')'", 15),
+            ("status = 500", "unexpected '=', use '==' to compare", 8),
+            ("(status > 1", "expected ')', found end of expression", 12),
+            ("response.code > 1", "unknown field 'response.code'", 1),
+            (
+                "host ~ 'api' and cookie('x')",
+                "unknown function 'cookie'",
+                18,
+            ),
+            (
+                "host ~ api",
+                "expected a quoted regex after '~', found 'api'",
+                8,
+            ),
+            ("host ~ '('", "invalid regex", 8),
+            ("status > 'x", "unterminated string", 10),
+            ("status >", "expected a value, found end of expression", 9),
+        ];
+        for (source, message, column) in cases {
+            let err = Expr::parse(source).expect_err(source);
+            assert!(
+                err.message.starts_with(message),
+                "{source}: {}",
+                err.message
+            );
+            assert_eq!(err.column, column, "{source}");
+        }
+
+        let rendered = parse_where("status = 500").expect_err("invalid");
+        assert_eq!(
+            rendered,
+            "unexpected '=', use '==' to compare at column 8\n  status = 500\n         ^"
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+use serde::{Serialize, Serializer};
+
+use crate::domain;
+use crate::expr::Expr;
+use crate::har::HarEntry;
+use crate::report::{entry_bytes, host_key, mime_type};
+
+/// A compiled regex that serializes as its source pattern.
+#[derive(Debug, Clone)]
+pub struct Pattern(pub Regex);
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+/// A response status (`404`) or status class (`4xx`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    Exact(i64),
+    Class(i64),
+}
+
+impl StatusFilter {
+    fn matches(self, status: i64) -> bool {
+        match self {
+            StatusFilter::Exact(code) => status == code,
+            StatusFilter::Class(class) => status / 100 == class && (100..=599).contains(&status),
+        }
+    }
+}
+
+impl fmt::Display for StatusFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusFilter::Exact(code) => write!(f, "{}", code),
+            StatusFilter::Class(class) => write!(f, "{}xx", class),
+        }
+    }
+}
+
+impl Serialize for StatusFilter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Entry filters applied before aggregation; every set filter must match (AND).
+///
+/// List filters match when any of their values does.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Filters {
+    /// Hosts, also matching their subdomains.
+    pub hosts: Vec<String>,
+    pub url_regex: Option<Pattern>,
+    pub exclude_url_regex: Option<Pattern>,
+    pub methods: Vec<String>,
+    pub statuses: Vec<StatusFilter>,
+    /// MIME types (`application/json`) or type wildcards (`image/*`, `image`).
+    pub mime_types: Vec<String>,
+    pub min_time_ms: Option<f64>,
+    pub min_bytes: Option<u64>,
+    pub pages: Vec<String>,
+    /// Window start, in ms after the file's first `startedDateTime` (see [`Filters::matches`]).
+    pub since_ms: Option<f64>,
+    /// Window end, in ms after the file's first `startedDateTime` (see [`Filters::matches`]).
+    pub until_ms: Option<f64>,
+    /// `--where` expression.
+    #[serde(rename = "where")]
+    pub where_expr: Option<Expr>,
+}
+
+impl Filters {
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+            && self.url_regex.is_none()
+            && self.exclude_url_regex.is_none()
+            && self.methods.is_empty()
+            && self.statuses.is_empty()
+            && self.mime_types.is_empty()
+            && self.min_time_ms.is_none()
+            && self.min_bytes.is_none()
+            && self.pages.is_empty()
+            && !self.has_window()
+            && self.where_expr.is_none()
+    }
+
+    pub fn has_window(&self) -> bool {
+        self.since_ms.is_some() || self.until_ms.is_some()
+    }
+
+    /// Whether `entry` passes every filter. `origin` is the first parseable `startedDateTime`
+    /// among the entries of its file, in `entries` order (`pages` are not consulted), and only
+    /// matters for the time window.
+    pub fn matches(&self, entry: &HarEntry, origin: Option<DateTime<FixedOffset>>) -> bool {
+        let url = &entry.request.url;
+        if !self.hosts.is_empty() && !domain::matches_any(&host_key(url), &self.hosts) {
+            return false;
+        }
+        if self
+            .url_regex
+            .as_ref()
+            .is_some_and(|re| !re.0.is_match(url))
+        {
+            return false;
+        }
+        if self
+            .exclude_url_regex
+            .as_ref()
+            .is_some_and(|re| re.0.is_match(url))
+        {
+            return false;
+        }
+        let method = entry.request.method.trim();
+        if !self.methods.is_empty() && !self.methods.iter().any(|m| m.eq_ignore_ascii_case(method))
+        {
+            return false;
+        }
+        let status = entry.response.status;
+        if !self.statuses.is_empty() && !self.statuses.iter().any(|s| s.matches(status)) {
+            return false;
+        }
+        if !self.mime_types.is_empty() {
+            let mime = mime_type(entry);
+            if !self.mime_types.iter().any(|p| mime_matches(p, &mime)) {
+                return false;
+            }
+        }
+        if self.min_time_ms.is_some_and(|min| entry.time < min) {
+            return false;
+        }
+        if self.min_bytes.is_some_and(|min| entry_bytes(entry) < min) {
+            return false;
+        }
+        if !self.pages.is_empty()
+            && !entry
+                .pageref
+                .as_ref()
+                .is_some_and(|page| self.pages.contains(page))
+        {
+            return false;
+        }
+        if self.has_window() {
+            let offset = match (origin, started_at(entry)) {
+                (Some(origin), Some(start)) => (start - origin).num_microseconds(),
+                _ => None,
+            };
+            // Entries that cannot be placed in time are outside any window.
+            let Some(offset) = offset.map(|us| us as f64 / 1000.0) else {
+                return false;
+            };
+            if self.since_ms.is_some_and(|since| offset < since)
+                || self.until_ms.is_some_and(|until| offset > until)
+            {
+                return false;
+            }
+        }
+        if self
+            .where_expr
+            .as_ref()
+            .is_some_and(|expr| !expr.matches(entry))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    match pattern.strip_suffix("/*") {
+        Some(kind) => mime.split('/').next() == Some(kind),
+        None if !pattern.contains('/') => mime.split('/').next() == Some(pattern.as_str()),
+        None => mime == pattern,
+    }
+}
+
+/// Parses an entry's `startedDateTime`.
+pub fn started_at(entry: &HarEntry) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(entry.started_date_time.trim()).ok()
+}
+
+pub fn parse_regex(s: &str) -> Result<Pattern, String> {
+    Regex::new(s)
+        .map(Pattern)
+        .map_err(|err| format!("invalid regex: {}", err))
+}
+
+pub fn parse_status(s: &str) -> Result<StatusFilter, String> {
+    let s = s.trim().to_ascii_lowercase();
+    let invalid = || format!("invalid status (expected e.g. 404 or 4xx): {}", s);
+    if let Some(class) = s.strip_suffix("xx") {
+        match class.parse() {
+            Ok(class @ 1..=5) => Ok(StatusFilter::Class(class)),
+            _ => Err(invalid()),
+        }
+    } else {
+        s.parse().map(StatusFilter::Exact).map_err(|_| invalid())
+    }
+}
+
+/// Parses a duration such as `500`, `500ms`, `1.5s` or `2m` into milliseconds.
+pub fn parse_duration_ms(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let (number, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1000.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60_000.0)
+    } else {
+        (s, 1.0)
+    };
+    match number.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(value * scale),
+        _ => Err(format!(
+            "invalid duration (expected e.g. 500ms, 1.5s or 2m): {}",
+            s
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::{HarRequest, HarResponse, HarResponseContent};
+
+    fn entry(url: &str, method: &str, status: i64, mime: &str, started: &str) -> HarEntry {
+        HarEntry {
+            started_date_time: started.to_string(),
+            time: 100.0,
+            request: HarRequest {
+                method: method.to_string(),
+                url: url.to_string(),
+                ..Default::default()
+            },
+            response: HarResponse {
+                status,
+                body_size: Some(2048),
+                content: Some(HarResponseContent {
+                    mime_type: mime.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            pageref: Some("page_1".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_filter_values() {
+        assert_eq!(parse_status("4xx"), Ok(StatusFilter::Class(4)));
+        assert_eq!(parse_status(" 500 "), Ok(StatusFilter::Exact(500)));
+        assert!(parse_status("9xx").is_err());
+        assert!(parse_status("oops").is_err());
+
+        assert_eq!(parse_duration_ms("250"), Ok(250.0));
+        assert_eq!(parse_duration_ms("250ms"), Ok(250.0));
+        assert_eq!(parse_duration_ms("1.5s"), Ok(1500.0));
+        assert_eq!(parse_duration_ms("2m"), Ok(120_000.0));
+        assert!(parse_duration_ms("-1s").is_err());
+        assert!(parse_duration_ms("soon").is_err());
+        assert!(parse_regex("(").is_err());
+    }
+
+    #[test]
+    fn filters_combine_with_and_semantics() {
+        let e = entry(
+            "https://api.example.com/v1/users?id=1",
+            "get",
+            404,
+            "application/json; charset=utf-8",
+            "2024-05-01T10:00:01.000Z",
+        );
+        assert!(Filters::default().matches(&e, None));
+
+        let mut filters = Filters {
+            hosts: vec!["example.com".to_string()],
+            url_regex: Some(parse_regex("/v1/").expect("regex")),
+            methods: vec!["GET".to_string()],
+            statuses: vec![StatusFilter::Exact(500), StatusFilter::Class(4)],
+            mime_types: vec!["application/*".to_string()],
+            min_time_ms: Some(100.0),
+            min_bytes: Some(1024),
+            pages: vec!["page_1".to_string()],
+            ..Default::default()
+        };
+        assert!(filters.matches(&e, None));
+
+        filters.exclude_url_regex = Some(parse_regex("users").expect("regex"));
+        assert!(!filters.matches(&e, None));
+        filters.exclude_url_regex = None;
+
+        for change in [
+            |f: &mut Filters| f.hosts = vec!["example.org".to_string()],
+            |f: &mut Filters| f.methods = vec!["POST".to_string()],
+            |f: &mut Filters| f.statuses = vec![StatusFilter::Class(5)],
+            |f: &mut Filters| f.mime_types = vec!["image".to_string()],
+            |f: &mut Filters| f.min_time_ms = Some(100.5),
+            |f: &mut Filters| f.min_bytes = Some(4096),
+            |f: &mut Filters| f.pages = vec!["page_2".to_string()],
+        ] {
+            let mut narrowed = filters.clone();
+            change(&mut narrowed);
+            assert!(!narrowed.matches(&e, None), "{:?}", narrowed);
+        }
+    }
+
+    #[test]
+    fn time_window_is_relative_to_origin() {
+        let origin = DateTime::parse_from_rfc3339("2024-05-01T10:00:00.000Z").ok();
+        let e = entry(
+            "https://a/",
+            "GET",
+            200,
+            "text/html",
+            "2024-05-01T12:00:01.500+02:00",
+        );
+        let window = |since, until| Filters {
+            since_ms: since,
+            until_ms: until,
+            ..Default::default()
+        };
+
+        assert!(window(Some(1000.0), Some(2000.0)).matches(&e, origin));
+        assert!(window(Some(1500.0), None).matches(&e, origin));
+        assert!(!window(Some(1600.0), None).matches(&e, origin));
+        assert!(!window(None, Some(1000.0)).matches(&e, origin));
+        assert!(!window(Some(0.0), None).matches(&e, None));
+
+        let undated = entry("https://a/", "GET", 200, "text/html", "");
+        assert!(!window(Some(0.0), None).matches(&undated, origin));
+    }
+}
@@ -1,46 +1,707 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::de::value::MapDeserializer;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use serde_json::Value;
+
+/// Vendor extension fields (`_initiator`, `_resourceType`, ...) kept verbatim.
+pub type CustomFields = BTreeMap<String, Value>;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Har {
     pub log: HarLog,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct HarLog {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub creator: Option<HarCreator>,
+    #[serde(default)]
+    pub browser: Option<HarCreator>,
+    #[serde(default)]
+    pub pages: Vec<HarPage>,
     pub entries: Vec<HarEntry>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
+}
+
+/// Shared shape of `log.creator` and `log.browser`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HarCreator {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub comment: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPage {
+    #[serde(default)]
+    pub started_date_time: String,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub page_timings: HarPageTimings,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPageTimings {
+    #[serde(default)]
+    pub on_content_load: Option<f64>,
+    #[serde(default)]
+    pub on_load: Option<f64>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct HarEntry {
+    #[serde(default)]
+    pub pageref: Option<String>,
+    #[serde(default)]
+    pub started_date_time: String,
     pub time: f64,
     pub request: HarRequest,
     pub response: HarResponse,
+    #[serde(default)]
+    pub cache: HarCache,
+    #[serde(default)]
+    pub timings: HarTimings,
+    #[serde(default, rename = "serverIPAddress")]
+    pub server_ip_address: Option<String>,
+    #[serde(default)]
+    pub connection: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct HarRequest {
+    #[serde(default)]
+    pub method: String,
     pub url: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarCookie>,
+    #[serde(default)]
+    pub headers: Vec<HarNameValue>,
+    #[serde(default)]
+    pub query_string: Vec<HarNameValue>,
+    #[serde(default)]
+    pub post_data: Option<HarPostData>,
+    #[serde(default, alias = "headers_size")]
+    pub headers_size: Option<i64>,
+    #[serde(default, alias = "body_size")]
+    pub body_size: Option<i64>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct HarResponse {
     #[serde(default)]
-    pub body_size: Option<i64>,
+    pub status: i64,
     #[serde(default)]
-    pub headers_size: Option<i64>,
+    pub status_text: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarCookie>,
+    #[serde(default)]
+    pub headers: Vec<HarNameValue>,
     #[serde(default)]
     pub content: Option<HarResponseContent>,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default, alias = "headers_size")]
+    pub headers_size: Option<i64>,
+    #[serde(default, alias = "body_size")]
+    pub body_size: Option<i64>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct HarResponseContent {
     #[serde(default)]
     pub size: Option<i64>,
+    #[serde(default)]
+    pub compression: Option<i64>,
+    #[serde(default)]
+    pub mime_type: String,
+    /// Kept by [`parse_har`]; the streaming path only keeps it when
+    /// [`StreamOptions::keep_content_text`] is set.
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub encoding: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
+}
+
+/// A `name`/`value` pair, used for both headers and query string parameters.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HarNameValue {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarCookie {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub expires: Option<String>,
+    #[serde(default)]
+    pub http_only: Option<bool>,
+    #[serde(default)]
+    pub secure: Option<bool>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub params: Vec<HarPostParam>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostParam {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub file_name: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarCache {
+    #[serde(default)]
+    pub before_request: Option<HarCacheEntry>,
+    #[serde(default)]
+    pub after_request: Option<HarCacheEntry>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HarCacheEntry {
+    #[serde(default)]
+    pub expires: Option<String>,
+    #[serde(default)]
+    pub last_access: Option<String>,
+    #[serde(default, rename = "eTag")]
+    pub e_tag: Option<String>,
+    #[serde(default)]
+    pub hit_count: Option<i64>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Per-phase timings in milliseconds; `-1` means the phase does not apply.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HarTimings {
+    #[serde(default = "not_applicable")]
+    pub blocked: f64,
+    #[serde(default = "not_applicable")]
+    pub dns: f64,
+    #[serde(default = "not_applicable")]
+    pub connect: f64,
+    #[serde(default = "not_applicable")]
+    pub send: f64,
+    #[serde(default = "not_applicable")]
+    pub wait: f64,
+    #[serde(default = "not_applicable")]
+    pub receive: f64,
+    #[serde(default = "not_applicable")]
+    pub ssl: f64,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub custom: CustomFields,
+}
+
+impl Default for HarTimings {
+    fn default() -> Self {
+        Self {
+            blocked: -1.0,
+            dns: -1.0,
+            connect: -1.0,
+            send: -1.0,
+            wait: -1.0,
+            receive: -1.0,
+            ssl: -1.0,
+            comment: None,
+            custom: CustomFields::new(),
+        }
+    }
+}
+
+fn not_applicable() -> f64 {
+    -1.0
+}
+
+/// Controls what [`stream_har`] keeps from each entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamOptions {
+    /// Keep `response.content.text`; skipped by default since it often holds large base64 bodies.
+    pub keep_content_text: bool,
+    /// Skip entries that do not match the HAR model instead of failing the whole file.
+    pub lenient: bool,
+}
+
+/// An entry dropped in lenient mode.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedEntry {
+    /// Position of the entry in its file's `log.entries`.
+    pub index: usize,
+    pub reason: String,
+    /// File the entry came from; set when the entry is recorded in a report.
+    pub source: Option<String>,
+}
+
+/// Everything [`stream_har`] reads besides the entries handed to the callback.
+#[derive(Debug, Default, Serialize)]
+pub struct Streamed {
+    /// Log-level fields; `entries` is left empty.
+    pub log: HarLog,
+    /// Entries skipped in lenient mode, in input order.
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// Reads one entry, skipping `response.content.text` unless `keep_content_text` is set.
+///
+/// The body is consumed as [`IgnoredAny`] and never allocated; every other field is borrowed
+/// from the entry's raw JSON and handed to the derived impls.
+#[derive(Debug, Clone, Copy)]
+struct EntrySeed {
+    keep_content_text: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for EntrySeed {
+    type Value = HarEntry;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<HarEntry, D::Error> {
+        if self.keep_content_text {
+            HarEntry::deserialize(deserializer)
+        } else {
+            deserializer.deserialize_map(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for EntrySeed {
+    type Value = HarEntry;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct HarEntry")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<HarEntry, A::Error> {
+        let mut fields = Vec::new();
+        let mut response = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "response" {
+                response = Some(map.next_value_seed(ResponseSeed)?);
+                // `response` is required, so the derived impl still has to see the key.
+                fields.push((key, empty_object()));
+            } else {
+                fields.push((key, map.next_value()?));
+            }
+        }
+        let mut entry: HarEntry = from_fields(fields)?;
+        if let Some(response) = response {
+            entry.response = response;
+        }
+        Ok(entry)
+    }
+}
+
+/// Reads `response`, handing `content` to [`ContentSeed`].
+struct ResponseSeed;
+
+impl<'de> DeserializeSeed<'de> for ResponseSeed {
+    type Value = HarResponse;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<HarResponse, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ResponseSeed {
+    type Value = HarResponse;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct HarResponse")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<HarResponse, A::Error> {
+        let mut fields = Vec::new();
+        let mut content = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "content" {
+                content = Some(map.next_value_seed(ContentSeed)?);
+            } else {
+                fields.push((key, map.next_value()?));
+            }
+        }
+        let mut response: HarResponse = from_fields(fields)?;
+        if let Some(content) = content {
+            response.content = content;
+        }
+        Ok(response)
+    }
+}
+
+/// Reads `response.content` without its `text`.
+struct ContentSeed;
+
+impl<'de> DeserializeSeed<'de> for ContentSeed {
+    type Value = Option<HarResponseContent>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ContentSeed {
+    type Value = Option<HarResponseContent>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct HarResponseContent")
+    }
+
+    fn visit_none<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut fields = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "text" {
+                map.next_value::<IgnoredAny>()?;
+            } else {
+                fields.push((key, map.next_value()?));
+            }
+        }
+        from_fields(fields).map(Some)
+    }
+}
+
+/// Builds `T` through its derived impl from object fields kept as raw JSON.
+fn from_fields<'de, T: Deserialize<'de>, E: de::Error>(
+    fields: Vec<(String, &'de RawValue)>,
+) -> std::result::Result<T, E> {
+    T::deserialize(MapDeserializer::<_, serde_json::Error>::new(
+        fields.into_iter(),
+    ))
+    .map_err(E::custom)
+}
+
+fn empty_object() -> &'static RawValue {
+    serde_json::from_str("{}").expect("valid JSON")
+}
+
+struct StreamContext<F> {
+    on_entry: F,
+    seed: EntrySeed,
+    lenient: bool,
+    skipped: Vec<SkippedEntry>,
+    /// Error returned by `on_entry`, kept so it is not flattened into a JSON error.
+    callback_error: Option<anyhow::Error>,
+    log: Option<HarLog>,
+}
+
+impl<F: FnMut(HarEntry) -> Result<()>> StreamContext<F> {
+    fn entry<E: de::Error>(&mut self, index: usize, raw: &RawValue) -> std::result::Result<(), E> {
+        // Parsing from the borrowed slice keeps unescaped strings zero-copy even though
+        // `#[serde(flatten)]` buffers the entry.
+        let mut deserializer = serde_json::Deserializer::from_str(raw.get());
+        let entry = match self.seed.deserialize(&mut deserializer) {
+            Ok(entry) => entry,
+            Err(err) if self.lenient => {
+                self.skipped.push(SkippedEntry {
+                    index,
+                    reason: skip_reason(&err),
+                    source: None,
+                });
+                return Ok(());
+            }
+            Err(err) => return Err(E::custom(format_args!("entry {}: {}", index, err))),
+        };
+        if let Err(err) = (self.on_entry)(entry) {
+            self.callback_error = Some(err);
+            return Err(E::custom("entry handler failed"));
+        }
+        Ok(())
+    }
+}
+
+/// Error message without serde_json's position, which is relative to the entry's own text.
+fn skip_reason(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    match message.rfind(" at line ") {
+        Some(at) => message[..at].to_string(),
+        None => message,
+    }
+}
+
+struct RootSeed<'a, F>(&'a mut StreamContext<F>);
+
+impl<'de, F: FnMut(HarEntry) -> Result<()>> DeserializeSeed<'de> for RootSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(HarEntry) -> Result<()>> Visitor<'de> for RootSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a HAR object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "log" {
+                map.next_value_seed(LogSeed(&mut *self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        if self.0.log.is_none() {
+            return Err(de::Error::missing_field("log"));
+        }
+        Ok(())
+    }
+}
+
+struct LogSeed<'a, F>(&'a mut StreamContext<F>);
+
+impl<'de, F: FnMut(HarEntry) -> Result<()>> DeserializeSeed<'de> for LogSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(HarEntry) -> Result<()>> Visitor<'de> for LogSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a HAR log object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        let mut log = HarLog::default();
+        let mut saw_entries = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "version" => log.version = map.next_value()?,
+                "creator" => log.creator = map.next_value()?,
+                "browser" => log.browser = map.next_value()?,
+                "pages" => log.pages = map.next_value()?,
+                "comment" => log.comment = map.next_value()?,
+                "entries" => {
+                    map.next_value_seed(EntriesSeed(&mut *self.0))?;
+                    saw_entries = true;
+                }
+                _ => {
+                    let value: Value = map.next_value()?;
+                    log.custom.insert(key, value);
+                }
+            }
+        }
+        if !saw_entries {
+            return Err(de::Error::missing_field("entries"));
+        }
+        self.0.log = Some(log);
+        Ok(())
+    }
+}
+
+struct EntriesSeed<'a, F>(&'a mut StreamContext<F>);
+
+impl<'de, F: FnMut(HarEntry) -> Result<()>> DeserializeSeed<'de> for EntriesSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(HarEntry) -> Result<()>> Visitor<'de> for EntriesSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of HAR entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let mut index = 0;
+        while let Some(raw) = seq.next_element::<Box<RawValue>>()? {
+            self.0.entry(index, &raw)?;
+            index += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a HAR document from `reader`, handing each `log.entries` item to `on_entry` as
+/// soon as it is read instead of collecting them.
+///
+/// Returns the remaining log fields with `entries` left empty, plus any entries skipped in
+/// lenient mode. Memory use is bounded by the largest single entry; `reader` should be
+/// buffered.
+pub fn stream_har<R, F>(reader: R, opts: &StreamOptions, on_entry: F) -> Result<Streamed>
+where
+    R: Read,
+    F: FnMut(HarEntry) -> Result<()>,
+{
+    let mut ctx = StreamContext {
+        on_entry,
+        seed: EntrySeed {
+            keep_content_text: opts.keep_content_text,
+        },
+        lenient: opts.lenient,
+        skipped: Vec::new(),
+        callback_error: None,
+        log: None,
+    };
+
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = RootSeed(&mut ctx)
+        .deserialize(&mut deserializer)
+        .and_then(|()| deserializer.end());
+    if let Some(err) = ctx.callback_error.take() {
+        return Err(err);
+    }
+    result.with_context(|| "failed to parse HAR JSON")?;
+
+    Ok(Streamed {
+        log: ctx.log.unwrap_or_default(),
+        skipped: ctx.skipped,
+    })
 }
 
+/// Parses a whole HAR document into memory, keeping every field including
+/// `response.content.text`.
 pub fn parse_har(bytes: &[u8]) -> Result<Har> {
-    serde_json::from_slice(bytes).with_context(|| "failed to parse HAR JSON")
+    let opts = StreamOptions {
+        keep_content_text: true,
+        ..Default::default()
+    };
+    parse_har_with(bytes, &opts)
+}
+
+/// Parses a whole HAR document into memory, collecting the entries [`stream_har`] reads.
+///
+/// Entries skipped in lenient mode are dropped; use [`stream_har`] to see them or to avoid
+/// holding every entry at once.
+pub fn parse_har_with<R: Read>(reader: R, opts: &StreamOptions) -> Result<Har> {
+    let mut entries = Vec::new();
+    let mut log = stream_har(reader, opts, |entry| {
+        entries.push(entry);
+        Ok(())
+    })?
+    .log;
+    log.entries = entries;
+    Ok(Har { log })
 }
 
 #[cfg(test)]
@@ -68,10 +729,287 @@ mod tests {
         assert_eq!(har.log.entries[0].response.headers_size, None);
     }
 
+    #[test]
+    fn parses_spec_camel_case_sizes() {
+        let json = r#"{
+          "log": {
+            "entries": [
+              {
+                "time": 1.0,
+                "request": { "url": "https://example.com" },
+                "response": {
+                  "bodySize": 512,
+                  "headersSize": 128,
+                  "content": { "size": 1024 }
+                }
+              }
+            ]
+          }
+        }"#;
+
+        let har = parse_har(json.as_bytes()).expect("HAR should parse");
+        let response = &har.log.entries[0].response;
+        assert_eq!(response.body_size, Some(512));
+        assert_eq!(response.headers_size, Some(128));
+        assert_eq!(response.content.as_ref().and_then(|c| c.size), Some(1024));
+    }
+
+    #[test]
+    fn accepts_snake_case_size_aliases() {
+        let json = r#"{
+          "log": {
+            "entries": [
+              {
+                "time": 1.0,
+                "request": { "url": "https://example.com" },
+                "response": { "body_size": 7, "headers_size": 3 }
+              }
+            ]
+          }
+        }"#;
+
+        let har = parse_har(json.as_bytes()).expect("HAR should parse");
+        assert_eq!(har.log.entries[0].response.body_size, Some(7));
+        assert_eq!(har.log.entries[0].response.headers_size, Some(3));
+    }
+
     #[test]
     fn rejects_malformed_json() {
         let bad = b"{ this is not valid json }";
         let err = parse_har(bad).expect_err("malformed JSON should fail");
         assert!(err.to_string().contains("failed to parse HAR JSON"));
     }
+
+    #[test]
+    fn parses_full_har_1_2_model() {
+        let json = r#"{
+          "log": {
+            "version": "1.2",
+            "creator": { "name": "WebInspector", "version": "537.36" },
+            "browser": { "name": "Chrome", "version": "133.0" },
+            "pages": [
+              {
+                "startedDateTime": "2025-03-04T10:15:22.104Z",
+                "id": "page_1",
+                "title": "Home",
+                "pageTimings": { "onContentLoad": 612.4, "onLoad": -1 }
+              }
+            ],
+            "entries": [
+              {
+                "pageref": "page_1",
+                "startedDateTime": "2025-03-04T10:15:22.108Z",
+                "time": 50.5,
+                "request": {
+                  "method": "POST",
+                  "url": "https://example.com/api?q=1",
+                  "httpVersion": "HTTP/1.1",
+                  "cookies": [{ "name": "sid", "value": "abc", "httpOnly": true }],
+                  "headers": [{ "name": "Content-Type", "value": "application/json" }],
+                  "queryString": [{ "name": "q", "value": "1" }],
+                  "postData": { "mimeType": "application/json", "text": "{}" },
+                  "headersSize": 150,
+                  "bodySize": 2
+                },
+                "response": {
+                  "status": 302,
+                  "statusText": "Found",
+                  "httpVersion": "HTTP/1.1",
+                  "cookies": [],
+                  "headers": [{ "name": "Location", "value": "/next" }],
+                  "content": { "size": 0, "mimeType": "text/plain", "text": "" },
+                  "redirectURL": "/next",
+                  "headersSize": 90,
+                  "bodySize": 0
+                },
+                "cache": { "beforeRequest": { "eTag": "x", "hitCount": 2 } },
+                "timings": { "send": 1, "wait": 40, "receive": 9.5, "_queued": 3 },
+                "serverIPAddress": "10.0.0.1",
+                "connection": "52492",
+                "_resourceType": "fetch"
+              }
+            ],
+            "_exporter": "custom"
+          }
+        }"#;
+
+        let opts = StreamOptions {
+            keep_content_text: true,
+            ..Default::default()
+        };
+        let har = parse_har_with(json.as_bytes(), &opts).expect("HAR should parse");
+        let log = &har.log;
+        assert_eq!(log.version, "1.2");
+        assert_eq!(
+            log.creator.as_ref().map(|c| c.name.as_str()),
+            Some("WebInspector")
+        );
+        assert_eq!(
+            log.browser.as_ref().map(|b| b.version.as_str()),
+            Some("133.0")
+        );
+        assert_eq!(log.pages[0].id, "page_1");
+        assert_eq!(log.pages[0].page_timings.on_content_load, Some(612.4));
+        assert_eq!(log.custom["_exporter"], "custom");
+
+        let entry = &log.entries[0];
+        assert_eq!(entry.pageref.as_deref(), Some("page_1"));
+        assert_eq!(entry.started_date_time, "2025-03-04T10:15:22.108Z");
+        assert_eq!(entry.server_ip_address.as_deref(), Some("10.0.0.1"));
+        assert_eq!(entry.connection.as_deref(), Some("52492"));
+        assert_eq!(entry.custom["_resourceType"], "fetch");
+
+        assert_eq!(entry.request.method, "POST");
+        assert_eq!(entry.request.cookies[0].http_only, Some(true));
+        assert_eq!(entry.request.headers[0].name, "Content-Type");
+        assert_eq!(entry.request.query_string[0].value, "1");
+        assert_eq!(
+            entry
+                .request
+                .post_data
+                .as_ref()
+                .map(|p| p.mime_type.as_str()),
+            Some("application/json")
+        );
+
+        assert_eq!(entry.response.status, 302);
+        assert_eq!(entry.response.redirect_url, "/next");
+        let content = entry.response.content.as_ref().expect("content");
+        assert_eq!(content.mime_type, "text/plain");
+        assert_eq!(content.text.as_deref(), Some(""));
+
+        let before = entry.cache.before_request.as_ref().expect("cache entry");
+        assert_eq!(before.e_tag.as_deref(), Some("x"));
+        assert_eq!(before.hit_count, Some(2));
+
+        assert_eq!(entry.timings.wait, 40.0);
+        assert_eq!(entry.timings.dns, -1.0);
+        assert_eq!(entry.timings.custom["_queued"], 3);
+    }
+
+    #[test]
+    fn stream_skips_content_text_by_default() {
+        let json = r#"{
+          "log": {
+            "entries": [
+              {
+                "time": 1.0,
+                "request": { "url": "https://example.com" },
+                "response": { "content": { "size": 4, "text": "aGVsbG8=", "mimeType": "text/plain" } }
+              }
+            ]
+          }
+        }"#;
+
+        let defaults = StreamOptions::default();
+        let har = parse_har_with(json.as_bytes(), &defaults).expect("HAR should parse");
+        let content = har.log.entries[0]
+            .response
+            .content
+            .as_ref()
+            .expect("content");
+        assert_eq!(content.text, None);
+        assert_eq!(content.mime_type, "text/plain");
+
+        // The skipped body is never deserialized, so even a malformed one is accepted.
+        let odd = json.replace(r#""aGVsbG8=""#, r#"{ "chunks": [1, 2] }"#);
+        let har = parse_har_with(odd.as_bytes(), &defaults).expect("HAR should parse");
+        let content = har.log.entries[0].response.content.as_ref();
+        assert_eq!(content.map(|c| c.size), Some(Some(4)));
+        assert!(parse_har(odd.as_bytes()).is_err());
+
+        // `parse_har` keeps every field.
+        let har = parse_har(json.as_bytes()).expect("HAR should parse");
+        let content = har.log.entries[0].response.content.as_ref();
+        assert_eq!(content.and_then(|c| c.text.as_deref()), Some("aGVsbG8="));
+    }
+
+    #[test]
+    fn stream_visits_entries_in_order_and_keeps_log_fields() {
+        let json = r#"{
+          "log": {
+            "entries": [
+              { "time": 1.0, "request": { "url": "https://a" }, "response": {} },
+              { "time": 2.0, "request": { "url": "https://b" }, "response": {} }
+            ],
+            "version": "1.2",
+            "_exporter": "custom"
+          },
+          "_top_level": true
+        }"#;
+
+        let mut urls = Vec::new();
+        let log = stream_har(json.as_bytes(), &StreamOptions::default(), |entry| {
+            urls.push(entry.request.url);
+            Ok(())
+        })
+        .expect("HAR should stream")
+        .log;
+
+        assert_eq!(urls, vec!["https://a", "https://b"]);
+        assert_eq!(log.version, "1.2");
+        assert_eq!(log.custom["_exporter"], "custom");
+        assert!(log.entries.is_empty());
+    }
+
+    #[test]
+    fn stream_reports_entry_index_and_handler_errors() {
+        let json = r#"{ "log": { "entries": [
+          { "time": 1.0, "request": { "url": "https://a" }, "response": {} },
+          { "request": { "url": "https://b" }, "response": {} }
+        ] } }"#;
+
+        let err = parse_har(json.as_bytes()).expect_err("missing time should fail");
+        let message = format!("{:#}", err);
+        assert!(message.contains("failed to parse HAR JSON"));
+        assert!(message.contains("entry 1: missing field `time`"));
+
+        let err = stream_har(json.as_bytes(), &StreamOptions::default(), |_| {
+            anyhow::bail!("stop here")
+        })
+        .expect_err("handler error should propagate");
+        assert_eq!(err.to_string(), "stop here");
+    }
+
+    #[test]
+    fn lenient_stream_skips_malformed_entries() {
+        let json = r#"{ "log": { "entries": [
+          { "time": null, "request": { "url": "https://a" }, "response": {} },
+          { "time": 2.0, "request": { "url": "https://b" }, "response": {} },
+          "garbage",
+          { "time": 3.0, "request": { "url": "https://c" }, "response": {} }
+        ] } }"#;
+        let opts = StreamOptions {
+            lenient: true,
+            ..Default::default()
+        };
+
+        let mut urls = Vec::new();
+        let streamed = stream_har(json.as_bytes(), &opts, |entry| {
+            urls.push(entry.request.url);
+            Ok(())
+        })
+        .expect("lenient mode should skip bad entries");
+
+        assert_eq!(urls, vec!["https://b", "https://c"]);
+        let indexes: Vec<usize> = streamed.skipped.iter().map(|s| s.index).collect();
+        assert_eq!(indexes, vec![0, 2]);
+        assert_eq!(
+            streamed.skipped[0].reason,
+            "invalid type: null, expected f64"
+        );
+        assert_eq!(
+            streamed.skipped[1].reason,
+            "invalid type: string \"garbage\", expected struct HarEntry"
+        );
+    }
+
+    #[test]
+    fn stream_requires_log_entries() {
+        let err = parse_har(br#"{ "log": { "version": "1.2" } }"#).expect_err("no entries");
+        assert!(format!("{:#}", err).contains("missing field `entries`"));
+
+        let err = parse_har(br#"{ "other": {} }"#).expect_err("no log");
+        assert!(format!("{:#}", err).contains("missing field `log`"));
+    }
 }
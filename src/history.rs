@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::budget::Unit;
+use crate::report::{PercentileMap, Report};
+
+/// Per-host numbers kept for each recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostSummary {
+    pub requests: usize,
+    pub p95_time_ms: f64,
+}
+
+/// One line of the history store: the summary of a recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub label: String,
+    pub sha: Option<String>,
+    /// RFC 3339 time stamp.
+    pub timestamp: String,
+    pub sources: Vec<String>,
+    pub entries: usize,
+    pub total_time_ms: f64,
+    pub total_bytes: u64,
+    pub p95_time_ms: f64,
+    pub hosts: BTreeMap<String, HostSummary>,
+}
+
+impl Record {
+    /// Summarizes `report`, which must be grouped by host with the 95th percentile.
+    pub fn new(
+        report: &Report,
+        label: String,
+        sha: Option<String>,
+        timestamp: DateTime<FixedOffset>,
+    ) -> Self {
+        let p95 = |percentiles: &PercentileMap| {
+            percentiles
+                .0
+                .iter()
+                .find(|(p, _)| *p == 95.0)
+                .map_or(0.0, |(_, value)| *value)
+        };
+        Self {
+            label,
+            sha,
+            timestamp: timestamp.to_rfc3339(),
+            sources: report.sources.clone(),
+            entries: report.entries,
+            total_time_ms: report.total_time_ms,
+            total_bytes: report.total_bytes,
+            p95_time_ms: p95(&report.time_percentiles),
+            hosts: report
+                .top_groups
+                .iter()
+                .map(|group| {
+                    let summary = HostSummary {
+                        requests: group.count,
+                        p95_time_ms: p95(&group.percentiles),
+                    };
+                    (group.key.clone(), summary)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Appends `record` as one JSON line, creating the store and its directory if needed.
+pub fn append(path: &Path, record: &Record) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to write history store: {}", path.display()))?;
+    }
+    let line = serde_json::to_string(record).with_context(|| "failed to serialize record")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to write history store: {}", path.display()))?;
+    writeln!(file, "{}", line)
+        .with_context(|| format!("failed to write history store: {}", path.display()))
+}
+
+/// Reads every record of the store, ordered by time stamp (recording order on ties).
+pub fn load(path: &Path) -> Result<Vec<Record>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read history store: {}", path.display()))?;
+    let mut records = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = serde_json::from_str(line).with_context(|| {
+            format!(
+                "failed to parse history store: {} line {}",
+                path.display(),
+                index + 1
+            )
+        })?;
+        let at = DateTime::parse_from_rfc3339(&record.timestamp).with_context(|| {
+            format!(
+                "failed to parse history store: {} line {}: invalid timestamp",
+                path.display(),
+                index + 1
+            )
+        })?;
+        records.push((at, record));
+    }
+    records.sort_by_key(|(at, _)| *at);
+    Ok(records.into_iter().map(|(_, record)| record).collect())
+}
+
+/// A point of a series; `value` is missing when the run has no data for it, e.g. an unseen host.
+#[derive(Debug, Serialize)]
+pub struct Point {
+    pub label: String,
+    pub sha: Option<String>,
+    pub timestamp: String,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Series {
+    pub metric: String,
+    pub host: Option<String>,
+    pub unit: Unit,
+    pub points: Vec<Point>,
+}
+
+impl Series {
+    fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.points.iter().filter_map(|p| p.value)
+    }
+
+    pub fn first(&self) -> Option<f64> {
+        self.values().next()
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.values().last()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Trend {
+    pub runs: usize,
+    pub series: Vec<Series>,
+}
+
+/// Builds the totals series and one p95 series per host seen in any run.
+pub fn trend(records: &[Record]) -> Trend {
+    let series =
+        |metric: &str, host: Option<&str>, unit, value: &dyn Fn(&Record) -> Option<f64>| Series {
+            metric: metric.to_string(),
+            host: host.map(str::to_string),
+            unit,
+            points: records
+                .iter()
+                .map(|record| Point {
+                    label: record.label.clone(),
+                    sha: record.sha.clone(),
+                    timestamp: record.timestamp.clone(),
+                    value: value(record),
+                })
+                .collect(),
+        };
+
+    let mut all = vec![
+        series("total_time", None, Unit::Ms, &|r| Some(r.total_time_ms)),
+        series("total_bytes", None, Unit::Bytes, &|r| {
+            Some(r.total_bytes as f64)
+        }),
+        series("p95_time", None, Unit::Ms, &|r| Some(r.p95_time_ms)),
+    ];
+    let hosts: BTreeSet<&String> = records.iter().flat_map(|r| r.hosts.keys()).collect();
+    for host in hosts {
+        all.push(series("p95_time", Some(host), Unit::Ms, &|r| {
+            r.hosts.get(host).map(|h| h.p95_time_ms)
+        }));
+    }
+    Trend {
+        runs: records.len(),
+        series: all,
+    }
+}
+
+/// Renders values as block characters scaled between the series minimum and maximum; missing
+/// values are blank.
+pub fn sparkline(values: &[Option<f64>]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let present = values.iter().flatten();
+    let min = present.clone().copied().fold(f64::INFINITY, f64::min);
+    let max = present.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if max <= min => BARS[0],
+            Some(v) => BARS[(((v - min) / (max - min)) * 7.0).round() as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(label: &str, timestamp: &str, total_time_ms: f64, hosts: &[(&str, f64)]) -> Record {
+        Record {
+            label: label.to_string(),
+            sha: None,
+            timestamp: timestamp.to_string(),
+            sources: vec!["run.har".to_string()],
+            entries: 3,
+            total_time_ms,
+            total_bytes: 1000,
+            p95_time_ms: 100.0,
+            hosts: hosts
+                .iter()
+                .map(|(host, p95)| {
+                    let summary = HostSummary {
+                        requests: 1,
+                        p95_time_ms: *p95,
+                    };
+                    (host.to_string(), summary)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn store_round_trips_in_timestamp_order() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("nested").join("history.jsonl");
+        let late = record("b", "2026-10-02T09:00:00+00:00", 200.0, &[]);
+        let early = record("a", "2026-10-02T10:00:00+02:00", 100.0, &[("a.test", 50.0)]);
+        append(&path, &late).unwrap();
+        append(&path, &early).unwrap();
+
+        assert_eq!(load(&path).unwrap(), vec![early, late]);
+
+        fs::write(&path, "{\"label\": 1}\n").unwrap();
+        let err = load(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("line 1"), "{:#}", err);
+    }
+
+    #[test]
+    fn builds_series_per_host() {
+        let records = vec![
+            record("1", "2026-10-01T00:00:00Z", 100.0, &[("a.test", 50.0)]),
+            record("2", "2026-10-02T00:00:00Z", 150.0, &[("b.test", 70.0)]),
+            record("3", "2026-10-03T00:00:00Z", 200.0, &[("a.test", 60.0)]),
+        ];
+        let trend = trend(&records);
+        assert_eq!(trend.runs, 3);
+        let names: Vec<(&str, Option<&str>)> = trend
+            .series
+            .iter()
+            .map(|s| (s.metric.as_str(), s.host.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("total_time", None),
+                ("total_bytes", None),
+                ("p95_time", None),
+                ("p95_time", Some("a.test")),
+                ("p95_time", Some("b.test")),
+            ]
+        );
+        let a = &trend.series[3];
+        let values: Vec<Option<f64>> = a.points.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![Some(50.0), None, Some(60.0)]);
+        assert_eq!((a.first(), a.last()), (Some(50.0), Some(60.0)));
+    }
+
+    #[test]
+    fn sparkline_scales_between_min_and_max() {
+        assert_eq!(
+            sparkline(&[Some(0.0), Some(7.0), None, Some(3.5), Some(14.0)]),
+            "▁▅ ▃█"
+        );
+        assert_eq!(sparkline(&[Some(5.0), Some(5.0)]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
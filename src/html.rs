@@ -0,0 +1,542 @@
+use std::fmt::Write;
+
+use crate::report::{
+    format_bytes, GroupRow, PercentileMap, PhaseStats, Report, ReportRow, SortBy, TimingPhase,
+    WaterfallRow,
+};
+
+/// Requests kept for the waterfall of each report.
+pub const WATERFALL_ROWS: usize = 1000;
+
+const STYLE: &str = r#"
+body { font: 14px/1.4 system-ui, sans-serif; margin: 24px; color: #1f2328; }
+h1 { font-size: 22px; }
+h2 { font-size: 18px; margin-top: 32px; border-bottom: 1px solid #d0d7de; }
+h3 { font-size: 15px; margin-top: 24px; }
+h4 { font-size: 14px; margin: 16px 0 4px; }
+dl.summary { display: grid; grid-template-columns: max-content auto; gap: 2px 16px; }
+dl.summary dt { font-weight: 600; }
+dl.summary dd { margin: 0; }
+table { border-collapse: collapse; margin: 8px 0; }
+th, td { padding: 3px 8px; border-bottom: 1px solid #eaeef2; text-align: left; }
+td.num, th.num { text-align: right; font-variant-numeric: tabular-nums; }
+td.url { max-width: 640px; overflow-wrap: anywhere; }
+table.sortable th { cursor: pointer; user-select: none; }
+table.sortable th.asc::after { content: " \25B2"; }
+table.sortable th.desc::after { content: " \25BC"; }
+.chart { max-width: 960px; }
+.bar-row { display: flex; align-items: center; gap: 8px; margin: 2px 0; }
+.bar-label { flex: 0 0 200px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.bar-track { flex: 1; height: 14px; background: #f6f8fa; position: relative; }
+.bar-value { flex: 0 0 260px; font-variant-numeric: tabular-nums; }
+.bar, .stack { height: 100%; display: flex; }
+.wf-bar { position: absolute; height: 100%; display: flex; min-width: 1px; }
+.bar span, .stack span, .wf-bar span { height: 100%; }
+.ph-blocked { background: #adb5bd; }
+.ph-dns { background: #2a9d8f; }
+.ph-connect { background: #e9c46a; }
+.ph-ssl { background: #b5838d; }
+.ph-send { background: #8ab17d; }
+.ph-wait { background: #4c7bd9; }
+.ph-receive { background: #e76f51; }
+.ph-total { background: #8c959f; }
+.legend span { display: inline-block; margin-right: 12px; }
+.legend i { display: inline-block; width: 10px; height: 10px; margin-right: 4px; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable").forEach(function (table) {
+  table.querySelectorAll("th").forEach(function (th, column) {
+    th.addEventListener("click", function () {
+      var ascending = !th.classList.contains("asc");
+      table.querySelectorAll("th").forEach(function (other) {
+        other.classList.remove("asc", "desc");
+      });
+      th.classList.add(ascending ? "asc" : "desc");
+      var body = table.tBodies[0];
+      var rows = Array.prototype.slice.call(body.rows);
+      var key = function (row) {
+        var cell = row.cells[column];
+        var sort = cell.getAttribute("data-sort");
+        return sort === null ? cell.textContent : parseFloat(sort);
+      };
+      rows.sort(function (a, b) {
+        var x = key(a), y = key(b);
+        var order = x < y ? -1 : x > y ? 1 : 0;
+        return ascending ? order : -order;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+"#;
+
+/// Renders reports as a single HTML document with inline styles and scripts, so it opens offline.
+pub fn render(reports: &[Report]) -> String {
+    let mut out =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>perf_tool report</title>\n<style>");
+    out.push_str(STYLE);
+    out.push_str("</style>\n</head>\n<body>\n<h1>perf_tool report</h1>\n");
+    for report in reports {
+        render_report(&mut out, report);
+    }
+    out.push_str("<script>");
+    out.push_str(SCRIPT);
+    out.push_str("</script>\n</body>\n</html>\n");
+    out
+}
+
+fn render_report(out: &mut String, report: &Report) {
+    let _ = writeln!(
+        out,
+        "<section>\n<h2>{}</h2>",
+        escape(&report.sources.join(", "))
+    );
+
+    out.push_str("<dl class=\"summary\">\n");
+    let mut item = |term: &str, value: String| {
+        let _ = writeln!(out, "<dt>{}</dt><dd>{}</dd>", term, escape(&value));
+    };
+    item("entries", report.entries.to_string());
+    if !report.filters.is_empty() {
+        item("excluded entries", report.excluded_entries.to_string());
+    }
+    item("total time", format_ms(report.total_time_ms));
+    item("total bytes", format_bytes(report.total_bytes));
+    for (p, value) in &report.time_percentiles.0 {
+        item(&PercentileMap::key(*p), format_ms(*value));
+    }
+    item("max time", format_ms(report.max_time_ms));
+    let statuses: Vec<String> = report
+        .status_classes
+        .iter()
+        .map(|(class, count)| format!("{} {}", class, count))
+        .collect();
+    item("statuses", statuses.join(", "));
+    if let Some(share) = &report.third_party {
+        item(
+            "third party",
+            format!(
+                "{} requests ({:.1}%), {} ({:.1}%), {} ({:.1}%)",
+                share.requests,
+                share.request_share * 100.0,
+                format_ms(share.time_ms),
+                share.time_share * 100.0,
+                format_bytes(share.bytes),
+                share.bytes_share * 100.0
+            ),
+        );
+    }
+    out.push_str("</dl>\n");
+
+    render_rows(out, "Slowest requests", &report.top_slowest);
+    render_rows(out, "Largest requests", &report.top_largest);
+    match report.sort_by {
+        Some(SortBy::Ttfb) => render_rows(out, "Slowest by TTFB", &report.top_sorted),
+        Some(SortBy::Throughput) => render_rows(out, "Lowest throughput", &report.top_sorted),
+        None => {}
+    }
+    if let Some(group_by) = &report.group_by {
+        render_groups(out, &group_by.label(), &report.top_groups);
+    }
+    render_phases(out, &report.timing_phases);
+    render_waterfall(out, &report.waterfall, report.entries);
+    out.push_str("</section>\n");
+}
+
+fn render_rows(out: &mut String, title: &str, rows: &[ReportRow]) {
+    let _ = writeln!(out, "<h3>{} ({})</h3>", escape(title), rows.len());
+    if rows.is_empty() {
+        return;
+    }
+    out.push_str(
+        "<table class=\"sortable\">\n<thead><tr><th>url</th><th class=\"num\">time</th>\
+         <th class=\"num\">bytes</th><th class=\"num\">ttfb</th><th class=\"num\">download</th>\
+         </tr></thead>\n<tbody>\n",
+    );
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "<tr><td class=\"url\">{}</td>{}{}{}{}</tr>",
+            escape(&row.url),
+            num_cell(row.time_ms, format_ms(row.time_ms)),
+            num_cell(row.bytes as f64, format_bytes(row.bytes)),
+            num_cell(row.ttfb_ms, format_ms(row.ttfb_ms)),
+            num_cell(row.download_ms, format_ms(row.download_ms))
+        );
+    }
+    out.push_str("</tbody>\n</table>\n");
+}
+
+fn render_groups(out: &mut String, label: &str, groups: &[GroupRow]) {
+    let _ = writeln!(
+        out,
+        "<h3>Groups by {} ({})</h3>",
+        escape(label),
+        groups.len()
+    );
+    if groups.is_empty() {
+        return;
+    }
+    out.push_str(
+        "<table class=\"sortable\">\n<thead><tr><th>group</th><th class=\"num\">requests</th>\
+         <th class=\"num\">total</th><th class=\"num\">avg</th>",
+    );
+    for (p, _) in &groups[0].percentiles.0 {
+        let _ = write!(out, "<th class=\"num\">{}</th>", PercentileMap::key(*p));
+    }
+    out.push_str("<th class=\"num\">max</th><th class=\"num\">bytes</th></tr></thead>\n<tbody>\n");
+    for group in groups {
+        let _ = write!(
+            out,
+            "<tr><td class=\"url\">{}</td>{}{}{}",
+            escape(&group.key),
+            num_cell(group.count as f64, group.count.to_string()),
+            num_cell(group.total_time_ms, format_ms(group.total_time_ms)),
+            num_cell(group.avg_time_ms, format_ms(group.avg_time_ms))
+        );
+        for (_, value) in &group.percentiles.0 {
+            out.push_str(&num_cell(*value, format_ms(*value)));
+        }
+        let _ = writeln!(
+            out,
+            "{}{}</tr>",
+            num_cell(group.max_time_ms, format_ms(group.max_time_ms)),
+            num_cell(group.total_bytes as f64, format_bytes(group.total_bytes))
+        );
+    }
+    out.push_str("</tbody>\n</table>\n");
+
+    // Average phase breakdown per group, scaled to the slowest group.
+    let widest = groups
+        .iter()
+        .map(|g| phase_avgs(&g.timing_phases).map(|(_, ms)| ms).sum::<f64>())
+        .fold(0.0, f64::max);
+    if widest <= 0.0 {
+        return;
+    }
+    out.push_str("<h3>Average timing phases per group</h3>\n");
+    legend(out);
+    out.push_str("<div class=\"chart\">\n");
+    for group in groups {
+        let phases: Vec<(TimingPhase, f64)> = phase_avgs(&group.timing_phases).collect();
+        let total: f64 = phases.iter().map(|(_, ms)| ms).sum();
+        let _ = write!(
+            out,
+            "<div class=\"bar-row\"><span class=\"bar-label\" title=\"{0}\">{0}</span>\
+             <div class=\"bar-track\"><div class=\"stack\" style=\"width:{1:.3}%\">",
+            escape(&group.key),
+            total / widest * 100.0
+        );
+        for (phase, ms) in &phases {
+            segment(out, *phase, *ms, total);
+        }
+        let _ = writeln!(
+            out,
+            "</div></div><span class=\"bar-value\">{}</span></div>",
+            format_ms(total)
+        );
+    }
+    out.push_str("</div>\n");
+}
+
+fn render_phases(out: &mut String, phases: &[PhaseStats]) {
+    out.push_str("<h3>Timing phases</h3>\n");
+    let widest = phases.iter().map(|s| s.p95_ms).fold(0.0, f64::max);
+    if widest <= 0.0 {
+        out.push_str("<p>No timing phases recorded.</p>\n");
+        return;
+    }
+    out.push_str("<div class=\"chart\">\n");
+    for stats in phases.iter().filter(|s| s.count > 0) {
+        let _ = writeln!(
+            out,
+            "<div class=\"bar-row\"><span class=\"bar-label\">{0}</span>\
+             <div class=\"bar-track\"><div class=\"bar ph-{0}\" style=\"width:{1:.3}%\"></div>\
+             <div class=\"bar ph-{0}\" style=\"width:{2:.3}%;opacity:0.35;position:absolute;top:0\">\
+             </div></div><span class=\"bar-value\">avg {3} &middot; p95 {4} &middot; n {5}</span></div>",
+            stats.phase.as_str(),
+            stats.avg_ms / widest * 100.0,
+            stats.p95_ms / widest * 100.0,
+            format_ms(stats.avg_ms),
+            format_ms(stats.p95_ms),
+            stats.count
+        );
+    }
+    out.push_str("</div>\n");
+}
+
+/// Renders the waterfall, one chart per input file: each file's rows are placed on that file's
+/// own timeline, so merged captures are not drawn as if they overlapped.
+fn render_waterfall(out: &mut String, rows: &[WaterfallRow], entries: usize) {
+    if rows.is_empty() {
+        return;
+    }
+    // Entries beyond the row limit or without a start time are left out.
+    let shown = if rows.len() < entries {
+        format!("{} of {} requests", rows.len(), entries)
+    } else {
+        format!("{} requests", rows.len())
+    };
+
+    // Rows are in input order, so each file's rows are contiguous.
+    let files: Vec<&[WaterfallRow]> = rows.chunk_by(|a, b| a.source == b.source).collect();
+    if let [rows] = files.as_slice() {
+        let (start, end) = timeline(rows);
+        let _ = writeln!(
+            out,
+            "<h3>Waterfall ({}, {})</h3>",
+            shown,
+            format_ms(end - start)
+        );
+        legend(out);
+        render_timeline(out, rows);
+        return;
+    }
+
+    let _ = writeln!(out, "<h3>Waterfall ({}, {} files)</h3>", shown, files.len());
+    legend(out);
+    for rows in files {
+        let (start, end) = timeline(rows);
+        let _ = writeln!(
+            out,
+            "<h4>{} ({} requests, {})</h4>",
+            escape(rows[0].source.as_deref().unwrap_or_default()),
+            rows.len(),
+            format_ms(end - start)
+        );
+        render_timeline(out, rows);
+    }
+}
+
+/// Earliest start and latest end of `rows`.
+fn timeline(rows: &[WaterfallRow]) -> (f64, f64) {
+    let start = rows
+        .iter()
+        .map(|r| r.start_ms)
+        .fold(f64::INFINITY, f64::min);
+    let end = rows
+        .iter()
+        .map(|r| r.start_ms + r.time_ms)
+        .fold(f64::NEG_INFINITY, f64::max);
+    (start, end)
+}
+
+fn render_timeline(out: &mut String, rows: &[WaterfallRow]) {
+    let (start, end) = timeline(rows);
+    let span = (end - start).max(1.0);
+    out.push_str("<div class=\"chart\">\n");
+    for row in rows {
+        let title = format!(
+            "{} {} ({}): starts at {}, takes {}",
+            row.method,
+            row.url,
+            row.status,
+            format_ms(row.start_ms),
+            format_ms(row.time_ms)
+        );
+        let _ = write!(
+            out,
+            "<div class=\"bar-row\" title=\"{}\"><span class=\"bar-label\">{} {}</span>\
+             <div class=\"bar-track\"><div class=\"wf-bar\" style=\"left:{:.3}%;width:{:.3}%\">",
+            escape(&title),
+            escape(&row.method),
+            escape(&row.url),
+            (row.start_ms - start) / span * 100.0,
+            row.time_ms / span * 100.0
+        );
+        // SSL time is part of connect, so it is not drawn separately.
+        let drawn: Vec<(TimingPhase, f64)> = row
+            .phases
+            .iter()
+            .filter(|p| p.phase != TimingPhase::Ssl && p.ms > 0.0)
+            .map(|p| (p.phase, p.ms))
+            .collect();
+        let total: f64 = drawn.iter().map(|(_, ms)| ms).sum();
+        if total > 0.0 {
+            for (phase, ms) in &drawn {
+                segment(out, *phase, *ms, total);
+            }
+        } else {
+            out.push_str("<span class=\"ph-total\" style=\"width:100%\"></span>");
+        }
+        let _ = writeln!(
+            out,
+            "</div></div><span class=\"bar-value\">{} &middot; {}</span></div>",
+            row.status,
+            format_ms(row.time_ms)
+        );
+    }
+    out.push_str("</div>\n");
+}
+
+/// Average duration of every phase except SSL, which is part of connect.
+fn phase_avgs(phases: &[PhaseStats]) -> impl Iterator<Item = (TimingPhase, f64)> + '_ {
+    phases
+        .iter()
+        .filter(|s| s.phase != TimingPhase::Ssl && s.count > 0 && s.avg_ms > 0.0)
+        .map(|s| (s.phase, s.avg_ms))
+}
+
+fn segment(out: &mut String, phase: TimingPhase, ms: f64, total: f64) {
+    let _ = write!(
+        out,
+        "<span class=\"ph-{0}\" style=\"width:{1:.3}%\" title=\"{0} {2}\"></span>",
+        phase.as_str(),
+        ms / total * 100.0,
+        format_ms(ms)
+    );
+}
+
+fn legend(out: &mut String) {
+    out.push_str("<p class=\"legend\">");
+    for phase in TimingPhase::ALL {
+        if phase != TimingPhase::Ssl {
+            let _ = write!(
+                out,
+                "<span><i class=\"ph-{0}\"></i>{0}</span>",
+                phase.as_str()
+            );
+        }
+    }
+    out.push_str("</p>\n");
+}
+
+fn num_cell(sort: f64, text: String) -> String {
+    format!(
+        "<td class=\"num\" data-sort=\"{}\">{}</td>",
+        sort,
+        escape(&text)
+    )
+}
+
+fn format_ms(ms: f64) -> String {
+    format!("{:.2} ms", ms)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::{HarEntry, HarRequest, HarResponse, HarTimings};
+    use crate::report::{build_report, GroupBy, GroupSpec, ReportBuilder, ReportOptions};
+
+    fn entry(url: &str, started: &str, wait: f64, receive: f64) -> HarEntry {
+        HarEntry {
+            started_date_time: started.to_string(),
+            time: wait + receive,
+            request: HarRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                ..Default::default()
+            },
+            response: HarResponse {
+                status: 200,
+                ..Default::default()
+            },
+            timings: HarTimings {
+                blocked: -1.0,
+                dns: -1.0,
+                connect: -1.0,
+                ssl: -1.0,
+                send: 0.0,
+                wait,
+                receive,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_self_contained_document_with_waterfall() {
+        let entries = vec![
+            entry(
+                "https://a.test/?q=<x>",
+                "2026-10-16T10:00:00.000Z",
+                60.0,
+                40.0,
+            ),
+            entry(
+                "https://b.test/app.js",
+                "2026-10-16T10:00:00.100Z",
+                30.0,
+                70.0,
+            ),
+            entry("https://b.test/late", "not a date", 10.0, 10.0),
+        ];
+        let opts = ReportOptions {
+            group_by: Some(GroupSpec(vec![GroupBy::Host])),
+            waterfall: WATERFALL_ROWS,
+            ..Default::default()
+        };
+        let report = build_report(&entries, &opts);
+        assert_eq!(report.waterfall.len(), 2);
+        assert_eq!(report.waterfall[1].start_ms, 100.0);
+
+        let html = render(&[report]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<link") && !html.contains("<script src"));
+        assert!(html.contains("https://a.test/?q=&lt;x&gt;"));
+        assert!(html.contains("<h3>Waterfall (2 of 3 requests, 200.00 ms)</h3>"));
+        // The second request starts halfway through the 200 ms span.
+        assert!(html.contains("class=\"wf-bar\" style=\"left:50.000%;width:50.000%\""));
+        assert!(html.contains(
+            "<span class=\"ph-wait\" style=\"width:30.000%\" title=\"wait 30.00 ms\"></span>"
+        ));
+        assert!(html.contains("<h3>Groups by host (2)</h3>"));
+        assert!(html.contains("<td class=\"num\" data-sort=\"100\">100.00 ms</td>"));
+    }
+
+    #[test]
+    fn renders_one_waterfall_per_merged_file() {
+        let opts = ReportOptions {
+            waterfall: WATERFALL_ROWS,
+            ..Default::default()
+        };
+        let mut builder = ReportBuilder::new(opts);
+        for (source, day) in [("monday.har", "13"), ("tuesday.har", "14")] {
+            builder.set_source(source);
+            for (path, ms) in [("/", "000"), ("/app.js", "050")] {
+                let started = format!("2026-10-{}T10:00:00.{}Z", day, ms);
+                builder.push(&entry(
+                    &format!("https://a.test{}", path),
+                    &started,
+                    50.0,
+                    50.0,
+                ));
+            }
+        }
+        let report = builder.finish();
+        assert_eq!(report.waterfall[2].start_ms, 0.0);
+
+        let html = render(&[report]);
+        assert!(html.contains("<h3>Waterfall (4 requests, 2 files)</h3>"));
+        assert!(html.contains("<h4>monday.har (2 requests, 150.00 ms)</h4>"));
+        assert!(html.contains("<h4>tuesday.har (2 requests, 150.00 ms)</h4>"));
+        assert_eq!(html.matches("<div class=\"chart\">").count(), 3);
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            escape("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
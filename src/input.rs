@@ -0,0 +1,290 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use flate2::bufread::MultiGzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const ZIP_MAGIC: &[u8] = &[0x50, 0x4b, 0x03, 0x04];
+
+/// Container format of an input file, detected from its magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Plain,
+    Gzip,
+    Zstd,
+    Zip,
+}
+
+impl Format {
+    pub fn detect(magic: &[u8]) -> Self {
+        if magic.starts_with(GZIP_MAGIC) {
+            Format::Gzip
+        } else if magic.starts_with(ZSTD_MAGIC) {
+            Format::Zstd
+        } else if magic.starts_with(ZIP_MAGIC) {
+            Format::Zip
+        } else {
+            Format::Plain
+        }
+    }
+}
+
+/// One input named on the command line, after directory and glob expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Source {
+    pub fn name(&self) -> String {
+        match self {
+            Source::Stdin => "<stdin>".to_string(),
+            Source::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// Expands `-` to stdin, directories to the HAR files beneath them and unmatched paths
+/// containing `*`, `?` or `[` as glob patterns.
+pub fn expand(paths: &[PathBuf]) -> Result<Vec<Source>> {
+    let mut sources = Vec::new();
+    for path in paths {
+        if path.as_os_str() == "-" {
+            sources.push(Source::Stdin);
+        } else if path.is_dir() {
+            let before = sources.len();
+            collect_dir(path, &mut sources)?;
+            if sources.len() == before {
+                bail!("no HAR files in directory: {}", path.display());
+            }
+        } else if !path.exists() && is_glob(path) {
+            let pattern = path.to_string_lossy();
+            let mut matches = Vec::new();
+            for entry in glob::glob(&pattern)
+                .with_context(|| format!("invalid glob pattern: {}", pattern))?
+            {
+                let path =
+                    entry.with_context(|| format!("failed to expand glob pattern: {}", pattern))?;
+                if path.is_file() {
+                    matches.push(path);
+                }
+            }
+            if matches.is_empty() {
+                bail!("no files match pattern: {}", pattern);
+            }
+            matches.sort();
+            sources.extend(matches.into_iter().map(Source::File));
+        } else {
+            sources.push(Source::File(path.clone()));
+        }
+    }
+    Ok(sources)
+}
+
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+fn collect_dir(dir: &Path, sources: &mut Vec<Source>) -> Result<()> {
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory: {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .with_context(|| format!("failed to read directory: {}", dir.display()))?;
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_dir(&path, sources)?;
+        } else if is_input_file(&path.to_string_lossy()) {
+            sources.push(Source::File(path));
+        }
+    }
+    Ok(())
+}
+
+/// Calls `visit` with the name and a decompressed reader for every HAR in `source`.
+///
+/// Plain, gzip and zstd inputs yield a single HAR; zip archives yield every `.har` member
+/// (which may itself be gzip or zstd compressed), in archive order.
+pub fn for_each_har<F>(source: &Source, visit: F) -> Result<()>
+where
+    F: FnMut(&str, &mut dyn Read) -> Result<()>,
+{
+    let name = source.name();
+    match source {
+        Source::Stdin => {
+            let mut reader = io::stdin().lock();
+            let magic = reader.fill_buf().with_context(|| "failed to read stdin")?;
+            if Format::detect(magic) == Format::Zip {
+                // Zip archives are read from the end, so stdin has to be buffered whole.
+                let mut bytes = Vec::new();
+                reader
+                    .read_to_end(&mut bytes)
+                    .with_context(|| "failed to read stdin")?;
+                read_zip(&name, Cursor::new(bytes), visit)
+            } else {
+                read_single(&name, reader, visit)
+            }
+        }
+        Source::File(path) => {
+            let file =
+                File::open(path).with_context(|| format!("failed to read file: {}", name))?;
+            let mut reader = BufReader::new(file);
+            let magic = reader
+                .fill_buf()
+                .with_context(|| format!("failed to read file: {}", name))?;
+            if Format::detect(magic) == Format::Zip {
+                read_zip(&name, reader, visit)
+            } else {
+                read_single(&name, reader, visit)
+            }
+        }
+    }
+}
+
+fn read_single<R, F>(name: &str, reader: R, mut visit: F) -> Result<()>
+where
+    R: BufRead,
+    F: FnMut(&str, &mut dyn Read) -> Result<()>,
+{
+    visit(name, &mut decompress(reader)?)
+}
+
+fn read_zip<R, F>(name: &str, reader: R, mut visit: F) -> Result<()>
+where
+    R: Read + Seek,
+    F: FnMut(&str, &mut dyn Read) -> Result<()>,
+{
+    let mut archive = zip::ZipArchive::new(reader)
+        .with_context(|| format!("failed to read zip archive: {}", name))?;
+    let mut found = 0;
+    for index in 0..archive.len() {
+        let member = archive
+            .by_index(index)
+            .with_context(|| format!("failed to read zip archive: {}", name))?;
+        if !is_har_member(member.name()) || member.is_dir() {
+            continue;
+        }
+        found += 1;
+        let member_name = format!("{}!{}", name, member.name());
+        let mut reader = decompress(BufReader::new(member))?;
+        visit(&member_name, &mut reader).with_context(|| format!("in {}", member_name))?;
+    }
+    if found == 0 {
+        bail!("no .har files in zip archive: {}", name);
+    }
+    Ok(())
+}
+
+fn is_har_member(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [".har", ".har.gz", ".har.zst"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+}
+
+/// Files picked up when a directory is given: HARs, compressed HARs and zip bundles.
+fn is_input_file(name: &str) -> bool {
+    is_har_member(name) || name.to_ascii_lowercase().ends_with(".zip")
+}
+
+/// Wraps `reader` in a gzip or zstd decoder when its magic bytes call for one.
+fn decompress<'a, R: BufRead + 'a>(mut reader: R) -> Result<Box<dyn Read + 'a>> {
+    let magic = reader.fill_buf().with_context(|| "failed to read input")?;
+    Ok(match Format::detect(magic) {
+        Format::Gzip => Box::new(MultiGzDecoder::new(reader)),
+        Format::Zstd => Box::new(
+            zstd::Decoder::with_buffer(reader).with_context(|| "failed to start zstd decoder")?,
+        ),
+        Format::Plain | Format::Zip => Box::new(reader),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_formats_from_magic_bytes() {
+        assert_eq!(Format::detect(b"\x1f\x8b\x08\x00"), Format::Gzip);
+        assert_eq!(Format::detect(b"\x28\xb5\x2f\xfd\x00"), Format::Zstd);
+        assert_eq!(Format::detect(b"PK\x03\x04"), Format::Zip);
+        assert_eq!(Format::detect(b"{\"log\""), Format::Plain);
+        assert_eq!(Format::detect(b""), Format::Plain);
+    }
+
+    #[test]
+    fn har_members_match_by_extension() {
+        assert!(is_har_member("run/page.har"));
+        assert!(is_har_member("PAGE.HAR"));
+        assert!(is_har_member("page.har.gz"));
+        assert!(!is_har_member("notes.txt"));
+        assert!(!is_har_member("page.har.bak"));
+    }
+
+    #[test]
+    fn expands_stdin_directories_and_globs() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let nested = dir.path().join("nested");
+        fs::create_dir(&nested).expect("mkdir");
+        for path in [
+            dir.path().join("b.har"),
+            dir.path().join("a.har.gz"),
+            dir.path().join("notes.txt"),
+            nested.join("c.zip"),
+        ] {
+            fs::write(path, b"").expect("write");
+        }
+
+        let sources = expand(&[PathBuf::from("-"), dir.path().to_path_buf()]).expect("expand");
+        assert_eq!(
+            sources,
+            [
+                Source::Stdin,
+                Source::File(dir.path().join("a.har.gz")),
+                Source::File(dir.path().join("b.har")),
+                Source::File(nested.join("c.zip")),
+            ]
+        );
+
+        let sources = expand(&[dir.path().join("*.har*")]).expect("glob");
+        assert_eq!(
+            sources,
+            [
+                Source::File(dir.path().join("a.har.gz")),
+                Source::File(dir.path().join("b.har")),
+            ]
+        );
+
+        let err = expand(&[dir.path().join("*.json")]).expect_err("no matches");
+        assert!(err.to_string().contains("no files match pattern"));
+        // Missing plain paths are left for the reader to report.
+        let missing = dir.path().join("missing.har");
+        let sources = expand(std::slice::from_ref(&missing)).expect("expand");
+        assert_eq!(sources, [Source::File(missing)]);
+    }
+
+    #[test]
+    fn decompresses_gzip_and_zstd() {
+        use std::io::Write;
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(b"hello").expect("gzip");
+        let gz = gz.finish().expect("gzip");
+        let zst = zstd::encode_all(&b"hello"[..], 0).expect("zstd");
+
+        for (input, expected) in [(gz, "hello"), (zst, "hello"), (b"plain".to_vec(), "plain")] {
+            let mut out = String::new();
+            decompress(&input[..])
+                .expect("decoder")
+                .read_to_string(&mut out)
+                .expect("read");
+            assert_eq!(out, expected);
+        }
+    }
+}
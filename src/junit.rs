@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use crate::budget::{BudgetResult, RuleResult};
+
+/// Renders budget results as JUnit XML: one test suite per checked input, one test case per rule.
+pub fn render(results: &[BudgetResult]) -> String {
+    let tests: usize = results.iter().map(|r| r.rules.len()).sum();
+    let failures: usize = results.iter().map(|r| r.failed_rules()).sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"perf_tool budget\" tests=\"{}\" failures=\"{}\">",
+        tests, failures
+    );
+    for result in results {
+        let suite = escape(&result.sources.join(", "));
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+            suite,
+            result.rules.len(),
+            result.failed_rules()
+        );
+        for rule in &result.rules {
+            let _ = write!(
+                out,
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape(&rule.name),
+                suite
+            );
+            if rule.passed {
+                out.push_str("/>\n");
+                continue;
+            }
+            let lines = failure_lines(rule);
+            let _ = writeln!(
+                out,
+                ">\n      <failure message=\"{}\" type=\"budget\">{}</failure>\n    </testcase>",
+                escape(&lines[0]),
+                escape(&lines.join("\n"))
+            );
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// One "actual vs expected" line per violating measurement, worst first.
+fn failure_lines(rule: &RuleResult) -> Vec<String> {
+    let mut failed: Vec<_> = rule.measurements.iter().filter(|m| !m.passed).collect();
+    failed.sort_by(|a, b| b.actual.total_cmp(&a.actual));
+    failed
+        .into_iter()
+        .map(|m| {
+            let group = m
+                .group
+                .as_ref()
+                .map(|g| format!("{}: ", g))
+                .unwrap_or_default();
+            format!(
+                "{}{}: actual {}, expected at most {}",
+                group,
+                rule.metric,
+                rule.unit.format(m.actual),
+                rule.unit.format(rule.max)
+            )
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0.
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::{Measurement, Unit};
+
+    fn rule(name: &str, max: f64, measurements: Vec<(Option<&str>, f64)>) -> RuleResult {
+        let measurements: Vec<Measurement> = measurements
+            .into_iter()
+            .map(|(group, actual)| Measurement {
+                group: group.map(str::to_string),
+                actual,
+                passed: actual <= max,
+            })
+            .collect();
+        RuleResult {
+            name: name.to_string(),
+            metric: "p95_time".to_string(),
+            per: None,
+            unit: Unit::Ms,
+            max,
+            passed: measurements.iter().all(|m| m.passed),
+            measurements,
+        }
+    }
+
+    #[test]
+    fn renders_rules_as_test_cases() {
+        let result = BudgetResult {
+            sources: vec!["a&b.har".to_string()],
+            passed: false,
+            rules: vec![
+                rule("p95_time <= 500.00 ms", 500.0, vec![(None, 120.0)]),
+                rule(
+                    "p95_time per host <= 100.00 ms",
+                    100.0,
+                    vec![
+                        (Some("a.com"), 150.0),
+                        (Some("b.com"), 50.0),
+                        (Some("c.com"), 300.0),
+                    ],
+                ),
+            ],
+        };
+
+        assert_eq!(
+            render(&[result]),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"perf_tool budget\" tests=\"2\" failures=\"1\">\n  \
+             <testsuite name=\"a&amp;b.har\" tests=\"2\" failures=\"1\">\n    \
+             <testcase name=\"p95_time &lt;= 500.00 ms\" classname=\"a&amp;b.har\"/>\n    \
+             <testcase name=\"p95_time per host &lt;= 100.00 ms\" classname=\"a&amp;b.har\">\n      \
+             <failure message=\"c.com: p95_time: actual 300.00 ms, expected at most 100.00 ms\" \
+             type=\"budget\">c.com: p95_time: actual 300.00 ms, expected at most 100.00 ms\n\
+             a.com: p95_time: actual 150.00 ms, expected at most 100.00 ms</failure>\n    \
+             </testcase>\n  \
+             </testsuite>\n\
+             </testsuites>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_markup_and_control_characters() {
+        assert_eq!(
+            escape("<a href=\"x\">'&'</a>\u{1}\n"),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;\n"
+        );
+    }
+}
@@ -0,0 +1,20 @@
+pub mod budget;
+pub mod compare;
+pub mod diff;
+mod domain;
+pub mod endpoint;
+pub mod expr;
+pub mod filter;
+pub mod har;
+pub mod history;
+pub mod html;
+pub mod input;
+pub mod junit;
+pub mod markdown;
+mod parsed_url;
+pub mod report;
+mod sketch;
+pub mod spanned;
+mod stats;
+pub mod suite;
+pub mod validate;
@@ -1,74 +1,1218 @@
-use std::fs;
+use std::io::BufReader;
 use std::path::PathBuf;
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
 
-mod har;
-mod report;
+use perf_tool::{
+    budget, compare, diff, endpoint, expr, filter, har, history, html, input, junit, markdown,
+    report, suite, validate,
+};
 
 #[derive(Parser, Debug)]
-#[command(name = "perf_tool", version, about = "Analyze HAR files")]
+#[command(
+    name = "perf_tool",
+    version,
+    about = "Analyze HAR files",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    // Path to the HAR file
-    path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+    // HAR files, directories or glob patterns (plain, .gz, .zst, or a .zip of HARs); `-` reads stdin
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    // Only include requests to these hosts (and their subdomains)
+    #[arg(long, value_delimiter = ',')]
+    host: Vec<String>,
+    // Only include requests whose URL matches this regex
+    #[arg(long, value_parser = filter::parse_regex)]
+    url_regex: Option<filter::Pattern>,
+    // Leave out requests whose URL matches this regex
+    #[arg(long, value_parser = filter::parse_regex)]
+    exclude_url_regex: Option<filter::Pattern>,
+    // Only include these request methods, e.g. GET,POST
+    #[arg(long, value_delimiter = ',')]
+    method: Vec<String>,
+    // Only include these statuses or status classes, e.g. 4xx,500
+    #[arg(long, value_delimiter = ',', value_parser = filter::parse_status)]
+    status: Vec<filter::StatusFilter>,
+    // Only include these MIME types, e.g. application/json,image/*
+    #[arg(long, value_delimiter = ',')]
+    mime: Vec<String>,
+    // Only include requests taking at least this many ms
+    #[arg(long)]
+    min_time: Option<f64>,
+    // Only include responses of at least this many bytes
+    #[arg(long)]
+    min_bytes: Option<u64>,
+    // Only include entries of these pages (pageref)
+    #[arg(long, value_delimiter = ',')]
+    page: Vec<String>,
+    // Only include entries starting this long after the first entry, e.g. 500ms, 2s, 1m
+    #[arg(long, value_parser = filter::parse_duration_ms)]
+    since: Option<f64>,
+    // Only include entries starting at most this long after the first entry
+    #[arg(long, value_parser = filter::parse_duration_ms)]
+    until: Option<f64>,
+    // Only include entries matching an expression, e.g. "status >= 500 or time > 800"
+    #[arg(long = "where", value_name = "EXPR", value_parser = expr::parse_where)]
+    where_expr: Option<expr::Expr>,
+    // Keep response bodies (response.content.text) so --where can match them; skipped by default
+    #[arg(long, default_value_t = false)]
+    keep_content_text: bool,
+    // Skip entries that do not match the HAR model instead of failing
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+    // Merge all inputs into one report instead of reporting each file separately
+    #[arg(long, default_value_t = false)]
+    merge: bool,
     // Show top N slowest requests
     #[arg(long, default_value_t = 10)]
     top: usize,
+    // Output format; html writes a self-contained page with a request waterfall, markdown suits PR comments
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    // Output JSON (same as --format json)
+    #[arg(long, default_value_t = false, conflicts_with = "format")]
+    json: bool,
+    // Group request metrics by one or more dimensions, e.g. host,status-class
+    #[arg(long, value_parser = report::parse_group_spec)]
+    group_by: Option<report::GroupSpec>,
+    // Add a top N list sorted by a derived per-request metric
+    #[arg(long, value_enum)]
+    sort_by: Option<report::SortBy>,
+    // Percentiles reported per group, e.g. 50,90,95,99,99.9
+    #[arg(long, value_delimiter = ',', default_value = "95", value_parser = parse_percentile)]
+    percentiles: Vec<f64>,
+    // How percentiles are computed
+    #[arg(long, value_enum, default_value_t = report::PercentileMethod::NearestRank)]
+    percentile_method: report::PercentileMethod,
+    // Domains treated as first-party, e.g. example.com,example.net
+    #[arg(long, value_delimiter = ',')]
+    first_party: Vec<String>,
+    // Prefix endpoint groups with the request method
+    #[arg(long, default_value_t = false)]
+    endpoint_method: bool,
+    // JSON file with endpoint path templates, e.g. {"templates": ["/users/{id}"]}
+    #[arg(long)]
+    endpoint_templates: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Html,
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum BudgetFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Check HAR files against the HAR 1.2 spec")]
+    Validate(ValidateArgs),
+    #[command(about = "Check HAR files against a performance budget")]
+    Check(CheckArgs),
+    #[command(about = "Compare a baseline HAR with a candidate HAR")]
+    Diff(DiffArgs),
+    #[command(
+        about = "Detect significant timing changes between repeated baseline and candidate runs"
+    )]
+    Compare(CompareArgs),
+    #[command(about = "Summarize endpoints across a test suite's HARs, one HAR per test")]
+    Suite(SuiteArgs),
+    #[command(about = "Append a run's summary to a local history store")]
+    Record(RecordArgs),
+    #[command(about = "Show metric trends over the runs in a history store")]
+    Trend(TrendArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct RecordArgs {
+    // HAR files, directories or glob patterns; all of them make up one run
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    // Name of the run, e.g. a build number or branch
+    #[arg(long)]
+    label: String,
+    // Git commit the run was made from
+    #[arg(long)]
+    sha: Option<String>,
+    // RFC 3339 time of the run, e.g. 2026-10-16T09:30:00Z; defaults to now
+    #[arg(long, value_parser = parse_timestamp)]
+    timestamp: Option<chrono::DateTime<chrono::FixedOffset>>,
+    // History store to append to (JSON lines)
+    #[arg(long, default_value = "perf_history.jsonl")]
+    store: PathBuf,
+    // Skip entries that do not match the HAR model instead of failing
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct TrendArgs {
+    // History store to read (JSON lines)
+    #[arg(long, default_value = "perf_history.jsonl")]
+    store: PathBuf,
+    // Only show the last N runs
+    #[arg(long)]
+    last: Option<usize>,
+    // Only show per-host series for these hosts
+    #[arg(long, value_delimiter = ',')]
+    host: Vec<String>,
     // Output JSON
     #[arg(long, default_value_t = false)]
     json: bool,
-    // Group request metrics by dimension
-    #[arg(long, value_enum)]
-    group_by: Option<report::GroupBy>,
+}
+
+fn run_record(args: RecordArgs) -> Result<()> {
+    let opts = report::ReportOptions {
+        top: 0,
+        group_by: Some(report::GroupSpec(vec![report::GroupBy::Host])),
+        all_groups: true,
+        percentiles: vec![95.0],
+        ..Default::default()
+    };
+    let stream_opts = har::StreamOptions {
+        lenient: args.lenient,
+        ..Default::default()
+    };
+    let mut builder = report::ReportBuilder::new(opts);
+    for source in input::expand(&args.paths)? {
+        read_source(&source, &stream_opts, std::slice::from_mut(&mut builder))?;
+    }
+    let report = builder.finish();
+
+    let timestamp = args
+        .timestamp
+        .unwrap_or_else(|| chrono::Utc::now().fixed_offset());
+    let record = history::Record::new(&report, args.label, args.sha, timestamp);
+    history::append(&args.store, &record)?;
+    println!(
+        "recorded {} ({} entries, {}) to {}",
+        record.label,
+        record.entries,
+        budget::Unit::Ms.format(record.total_time_ms),
+        args.store.display()
+    );
+    Ok(())
+}
+
+fn run_trend(args: TrendArgs) -> Result<()> {
+    let mut records = history::load(&args.store)?;
+    if let Some(last) = args.last {
+        records.drain(..records.len().saturating_sub(last));
+    }
+    let mut trend = history::trend(&records);
+    if !args.host.is_empty() {
+        trend.series.retain(|series| {
+            series
+                .host
+                .as_ref()
+                .is_none_or(|host| args.host.iter().any(|h| h.eq_ignore_ascii_case(host)))
+        });
+    }
+
+    if args.json {
+        let out = serde_json::to_string_pretty(&trend)
+            .with_context(|| "failed to serialize JSON output")?;
+        println!("{}", out);
+    } else {
+        render_trend(&trend);
+    }
+    Ok(())
+}
+
+fn render_trend(trend: &history::Trend) {
+    let labels = trend.series.first().map(|series| {
+        let first = series.points.first().map_or("", |p| p.label.as_str());
+        let last = series.points.last().map_or("", |p| p.label.as_str());
+        format!(" ({} .. {})", first, last)
+    });
+    println!("runs: {}{}", trend.runs, labels.unwrap_or_default());
+    if trend.runs == 0 {
+        return;
+    }
+
+    let names: Vec<String> = trend
+        .series
+        .iter()
+        .map(|series| match &series.host {
+            Some(host) => format!("{} {}", series.metric, host),
+            None => series.metric.clone(),
+        })
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0);
+    for (name, series) in names.iter().zip(&trend.series) {
+        let values: Vec<Option<f64>> = series.points.iter().map(|p| p.value).collect();
+        let change = match (series.first(), series.last()) {
+            (Some(first), Some(last)) => {
+                let percent = if first != 0.0 {
+                    format!("  ({:+.1}%)", (last - first) / first * 100.0)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{} -> {}{}",
+                    series.unit.format(first),
+                    series.unit.format(last),
+                    percent
+                )
+            }
+            _ => String::new(),
+        };
+        println!(
+            "  {:<width$}  {}  {}",
+            name,
+            history::sparkline(&values),
+            change,
+            width = width
+        );
+    }
+}
+
+fn parse_timestamp(s: &str) -> Result<chrono::DateTime<chrono::FixedOffset>, String> {
+    chrono::DateTime::parse_from_rfc3339(s.trim())
+        .map_err(|err| format!("invalid RFC 3339 timestamp {}: {}", s, err))
+}
+
+#[derive(clap::Args, Debug)]
+struct SuiteArgs {
+    // HAR files, directories or glob patterns; each HAR is one test
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    // Show only the top N endpoints by total time
+    #[arg(long)]
+    top: Option<usize>,
+    // List every test that hit each endpoint in text output
+    #[arg(long, default_value_t = false)]
+    list_tests: bool,
+    // Percentiles reported per endpoint, e.g. 50,90,95,99,99.9
+    #[arg(long, value_delimiter = ',', default_value = "50,95,99", value_parser = parse_percentile)]
+    percentiles: Vec<f64>,
+    // How percentiles are computed
+    #[arg(long, value_enum, default_value_t = report::PercentileMethod::NearestRank)]
+    percentile_method: report::PercentileMethod,
+    // Prefix endpoint groups with the request method
+    #[arg(long, default_value_t = false)]
+    endpoint_method: bool,
+    // JSON file with endpoint path templates, e.g. {"templates": ["/users/{id}"]}
+    #[arg(long)]
+    endpoint_templates: Option<PathBuf>,
+    // Skip entries that do not match the HAR model instead of failing
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+    // Output JSON
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+fn run_suite(args: SuiteArgs) -> Result<()> {
+    let templates = match &args.endpoint_templates {
+        Some(path) => endpoint::load_templates(path)?,
+        None => Vec::new(),
+    };
+    let opts = report::ReportOptions {
+        top: 0,
+        group_by: Some(report::GroupSpec(vec![report::GroupBy::Endpoint])),
+        all_groups: true,
+        percentiles: args.percentiles,
+        percentile_method: args.percentile_method,
+        endpoints: endpoint::EndpointRules {
+            with_method: args.endpoint_method,
+            templates,
+        },
+        ..Default::default()
+    };
+    let stream_opts = har::StreamOptions {
+        lenient: args.lenient,
+        ..Default::default()
+    };
+
+    let mut builder = report::ReportBuilder::new(opts.clone());
+    let mut index = suite::SuiteIndex::default();
+    for source in input::expand(&args.paths)? {
+        read_entries(
+            &source,
+            &stream_opts,
+            std::slice::from_mut(&mut builder),
+            |test, entry| index.push(test, entry, &opts),
+        )?;
+    }
+    let suite = suite::summarize(builder.finish(), index, args.top);
+
+    if args.json {
+        let out = serde_json::to_string_pretty(&suite)
+            .with_context(|| "failed to serialize JSON output")?;
+        println!("{}", out);
+    } else {
+        render_suite(&suite, args.list_tests);
+    }
+    Ok(())
+}
+
+fn render_suite(suite: &suite::Suite, list_tests: bool) {
+    let report = &suite.report;
+    println!("tests: {}", suite.tests);
+    println!("entries: {}", report.entries);
+    println!("total_time_ms: {:.2}", report.total_time_ms);
+    let percentiles: Vec<String> = report
+        .time_percentiles
+        .0
+        .iter()
+        .map(|(p, value)| format!("{} {:.2}", report::PercentileMap::key(*p), value))
+        .collect();
+    println!("time ms: {}", percentiles.join("  "));
+
+    println!("\nendpoints ({}):", suite.endpoints.len());
+    for endpoint in &suite.endpoints {
+        let group = &endpoint.group;
+        let percentiles: String = group
+            .percentiles
+            .0
+            .iter()
+            .map(|(p, value)| format!("{:>8.2} ms {}  ", value, report::PercentileMap::key(*p)))
+            .collect();
+        println!(
+            "{:>5} req  {:>4} tests  {}{}",
+            group.count,
+            endpoint.tests.len(),
+            percentiles,
+            group.key
+        );
+        if let Some(worst) = &endpoint.worst_test {
+            println!(
+                "{:>7}worst: {} ({} req, max {:.2} ms)",
+                "", worst.test, worst.requests, worst.max_time_ms
+            );
+        }
+        if list_tests {
+            for test in &endpoint.tests {
+                println!(
+                    "{:>9}{:>4} req  {:>8.2} ms max  {}",
+                    "", test.requests, test.max_time_ms, test.test
+                );
+            }
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct CompareArgs {
+    // Baseline runs: HAR files, directories or glob patterns
+    #[arg(long, required = true, num_args = 1..)]
+    baseline: Vec<PathBuf>,
+    // Candidate runs: HAR files, directories or glob patterns
+    #[arg(long, required = true, num_args = 1..)]
+    candidate: Vec<PathBuf>,
+    // Dimensions compared, e.g. endpoint,host
+    #[arg(long, default_value = "endpoint,host", value_parser = report::parse_group_spec)]
+    group_by: report::GroupSpec,
+    // False discovery rate for the Benjamini-Hochberg adjusted Mann-Whitney p-values
+    #[arg(long, default_value_t = 0.05, value_parser = parse_probability)]
+    alpha: f64,
+    // Confidence level of the bootstrap interval of the median shift
+    #[arg(long, default_value_t = 0.95, value_parser = parse_probability)]
+    confidence: f64,
+    // Bootstrap resamples per group
+    #[arg(long, default_value_t = 2000)]
+    resamples: usize,
+    // Seed for the bootstrap, so results are reproducible
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    // Prefix endpoint groups with the request method
+    #[arg(long, default_value_t = false)]
+    endpoint_method: bool,
+    // JSON file with endpoint path templates, e.g. {"templates": ["/users/{id}"]}
+    #[arg(long)]
+    endpoint_templates: Option<PathBuf>,
+    // Skip entries that do not match the HAR model instead of failing
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+    // Output JSON
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+fn run_compare(args: CompareArgs) -> Result<()> {
+    let templates = match &args.endpoint_templates {
+        Some(path) => endpoint::load_templates(path)?,
+        None => Vec::new(),
+    };
+    let opts = compare::CompareOptions {
+        dimensions: args.group_by.0,
+        alpha: args.alpha,
+        confidence: args.confidence,
+        resamples: args.resamples,
+        seed: args.seed,
+        grouping: report::ReportOptions {
+            endpoints: endpoint::EndpointRules {
+                with_method: args.endpoint_method,
+                templates,
+            },
+            ..Default::default()
+        },
+    };
+    let stream_opts = har::StreamOptions {
+        lenient: args.lenient,
+        ..Default::default()
+    };
+    let read_runs = |paths: &[PathBuf]| -> Result<compare::Samples> {
+        let mut samples = compare::Samples::new(&opts);
+        for source in input::expand(paths)? {
+            read_entries(&source, &stream_opts, &mut [], |name, entry| {
+                if samples.runs.last().map(String::as_str) != Some(name) {
+                    samples.start_run(name);
+                }
+                samples.push(entry);
+            })?;
+        }
+        Ok(samples)
+    };
+    let baseline = read_runs(&args.baseline)?;
+    let candidate = read_runs(&args.candidate)?;
+    let comparison = compare::compare(baseline, candidate, &opts);
+
+    if args.json {
+        let out = serde_json::to_string_pretty(&comparison)
+            .with_context(|| "failed to serialize JSON output")?;
+        println!("{}", out);
+    } else {
+        render_comparison(&comparison);
+    }
+    Ok(())
+}
+
+fn render_comparison(comparison: &compare::Comparison) {
+    for (label, side) in [
+        ("baseline: ", &comparison.baseline),
+        ("candidate:", &comparison.candidate),
+    ] {
+        println!(
+            "{} {} runs, {} entries",
+            label,
+            side.runs.len(),
+            side.entries
+        );
+    }
+    println!(
+        "tested {} groups ({} skipped in fewer than 2 runs); alpha {} (Benjamini-Hochberg), {}% bootstrap CI, {} resamples",
+        comparison.tested_groups,
+        comparison.untested_groups,
+        comparison.alpha,
+        comparison.confidence * 100.0,
+        comparison.resamples
+    );
+
+    if comparison.regressions.is_empty() && comparison.improvements.is_empty() {
+        println!("\nno significant changes");
+        return;
+    }
+    for (label, changes) in [
+        ("regressions", &comparison.regressions),
+        ("improvements", &comparison.improvements),
+    ] {
+        if changes.is_empty() {
+            continue;
+        }
+        println!("\n{} {}:", label, changes.len());
+        for change in changes {
+            println!(
+                "{:>+10.2} ms  [{:+.2}, {:+.2}]  p={:.4} (adjusted {:.4})  delta={:+.2} ({})  {} {}  ({:.2} -> {:.2} ms median, runs={}/{})",
+                change.median_shift_ms,
+                change.ci_low_ms,
+                change.ci_high_ms,
+                change.p_value,
+                change.adjusted_p_value,
+                change.effect_size,
+                change.magnitude.as_str(),
+                change.dimension.as_str(),
+                change.key,
+                change.baseline.median_ms,
+                change.candidate.median_ms,
+                change.baseline.runs,
+                change.candidate.runs
+            );
+        }
+    }
+}
+
+fn parse_probability(s: &str) -> Result<f64, String> {
+    let p: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid probability: {}", s))?;
+    if p > 0.0 && p < 1.0 {
+        Ok(p)
+    } else {
+        Err(format!("probability must be in (0, 1): {}", s))
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    // Baseline HAR file, directory or glob pattern; several files are merged
+    baseline: PathBuf,
+    // Candidate HAR file, directory or glob pattern; several files are merged
+    candidate: PathBuf,
+    // Also compare groups by one or more dimensions, e.g. host,status-class
+    #[arg(long, value_parser = report::parse_group_spec)]
+    group_by: Option<report::GroupSpec>,
+    // Show top N added, removed and changed requests and groups
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    // Match requests on method, scheme, host and path only
+    #[arg(long, default_value_t = false)]
+    ignore_query: bool,
+    // Skip entries that do not match the HAR model instead of failing
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+    // Output JSON
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+fn run_diff(args: DiffArgs) -> Result<()> {
+    let opts = report::ReportOptions {
+        group_by: args.group_by,
+        all_groups: true,
+        ..Default::default()
+    };
+    let stream_opts = har::StreamOptions {
+        lenient: args.lenient,
+        ..Default::default()
+    };
+    let match_opts = diff::MatchOptions {
+        ignore_query: args.ignore_query,
+    };
+    let read_capture = |path: &PathBuf| -> Result<diff::Capture> {
+        let mut builder = report::ReportBuilder::new(opts.clone());
+        let mut requests = Vec::new();
+        for source in input::expand(std::slice::from_ref(path))? {
+            read_entries(
+                &source,
+                &stream_opts,
+                std::slice::from_mut(&mut builder),
+                |_, entry| requests.push(diff::RequestRecord::new(entry, match_opts)),
+            )?;
+        }
+        Ok(diff::Capture {
+            report: builder.finish(),
+            requests,
+        })
+    };
+    let baseline = read_capture(&args.baseline)?;
+    let candidate = read_capture(&args.candidate)?;
+    let diff = diff::diff(baseline, candidate, args.top);
+
+    if args.json {
+        let out = serde_json::to_string_pretty(&diff)
+            .with_context(|| "failed to serialize JSON output")?;
+        println!("{}", out);
+    } else {
+        render_diff(&diff);
+    }
+    Ok(())
+}
+
+fn render_diff(diff: &diff::Diff) {
+    println!(
+        "baseline:  {} ({} entries)",
+        diff.baseline.sources.join(", "),
+        diff.baseline.entries
+    );
+    println!(
+        "candidate: {} ({} entries)",
+        diff.candidate.sources.join(", "),
+        diff.candidate.entries
+    );
+    println!(
+        "requests: {} matched, {} added, {} removed",
+        diff.matched_requests, diff.added_requests, diff.removed_requests
+    );
+
+    let totals = &diff.totals;
+    println!("\ntotals:");
+    println!(
+        "  {:<14} {:>12} -> {:<12} {:>+12}",
+        "entries", totals.entries.baseline, totals.entries.candidate, totals.entries.delta
+    );
+    println!(
+        "  {:<14} {:>12.2} -> {:<12.2} {:>+12.2}",
+        "total_time_ms",
+        totals.total_time_ms.baseline,
+        totals.total_time_ms.candidate,
+        totals.total_time_ms.delta
+    );
+    println!(
+        "  {:<14} {:>12} -> {:<12} {:>12}",
+        "total_bytes",
+        report::format_bytes(totals.total_bytes.baseline as u64),
+        report::format_bytes(totals.total_bytes.candidate as u64),
+        format_bytes_delta(totals.total_bytes.delta)
+    );
+
+    println!("\nchanged {}:", diff.changed.len());
+    for request in &diff.changed {
+        println!(
+            "{:>+10.2} ms {:>12}  {}  ({:.2} -> {:.2} ms)",
+            request.time_ms.delta,
+            format_bytes_delta(request.bytes.delta),
+            request.key,
+            request.time_ms.baseline,
+            request.time_ms.candidate
+        );
+    }
+
+    for (label, requests) in [("added", &diff.added), ("removed", &diff.removed)] {
+        println!("\n{} {}:", label, requests.len());
+        for request in requests {
+            println!(
+                "{:>8.2} ms {:>10}  {}",
+                request.time_ms,
+                report::format_bytes(request.bytes),
+                request.key
+            );
+        }
+    }
+
+    if let Some(group_by) = &diff.group_by {
+        println!("\ngroups by {} ({}):", group_by.label(), diff.groups.len());
+        for group in &diff.groups {
+            println!(
+                "{:>+10.2} ms total  {:>+4} req  {:>+10.2} ms p95  {:>12}  {}",
+                group.total_time_ms.delta,
+                group.count.delta,
+                group.p95_time_ms.delta,
+                format_bytes_delta(group.total_bytes.delta),
+                group.key
+            );
+        }
+    }
+}
+
+/// A byte delta with an explicit sign, e.g. `+1.50 KB` or `-200 B`.
+fn format_bytes_delta(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, report::format_bytes(delta.unsigned_abs()))
+}
+
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    // HAR files, directories or glob patterns; `-` reads stdin
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    // Exit with an error when any problem is found
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+    // Output JSON
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    // HAR files, directories or glob patterns; `-` reads stdin
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    // Budget file (TOML or JSON) with the rules to check
+    #[arg(long)]
+    budget: PathBuf,
+    // Skip entries that do not match the HAR model instead of failing
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+    // Check all inputs as one capture instead of each file separately
+    #[arg(long, default_value_t = false)]
+    merge: bool,
+    // Output format; markdown suits PR comments
+    #[arg(long, value_enum, default_value_t = BudgetFormat::Text)]
+    format: BudgetFormat,
+    // Output JSON (same as --format json)
+    #[arg(long, default_value_t = false, conflicts_with = "format")]
+    json: bool,
+    // Also write the results as JUnit XML to this file, one test case per rule
+    #[arg(long)]
+    junit: Option<PathBuf>,
+}
+
+fn run_check(args: CheckArgs) -> Result<()> {
+    let budget = budget::load(&args.budget)?;
+    let sources = input::expand(&args.paths)?;
+    let stream_opts = har::StreamOptions {
+        lenient: args.lenient,
+        ..Default::default()
+    };
+
+    let mut results = Vec::new();
+    for group in input_groups(&sources, args.merge) {
+        let mut builders: Vec<report::ReportBuilder> = budget
+            .report_options()
+            .into_iter()
+            .map(report::ReportBuilder::new)
+            .collect();
+        for source in group {
+            read_source(source, &stream_opts, &mut builders)?;
+        }
+        let reports: Vec<report::Report> = builders.into_iter().map(|b| b.finish()).collect();
+        results.push(budget.evaluate(&reports));
+    }
+
+    if let Some(path) = &args.junit {
+        std::fs::write(path, junit::render(&results))
+            .with_context(|| format!("failed to write JUnit report: {}", path.display()))?;
+    }
+
+    let format = if args.json {
+        BudgetFormat::Json
+    } else {
+        args.format
+    };
+    match format {
+        BudgetFormat::Json => {
+            let out = match results.as_slice() {
+                [result] => serde_json::to_string_pretty(result),
+                results => serde_json::to_string_pretty(results),
+            }
+            .with_context(|| "failed to serialize JSON output")?;
+            println!("{}", out);
+        }
+        BudgetFormat::Markdown => print!("{}", markdown::render_budget(&results)),
+        BudgetFormat::Text => {
+            for (index, result) in results.iter().enumerate() {
+                if results.len() > 1 {
+                    if index > 0 {
+                        println!();
+                    }
+                    println!("== {} ==", result.sources.join(", "));
+                }
+                render_budget(result);
+            }
+        }
+    }
+
+    let failed: usize = results.iter().map(|r| r.failed_rules()).sum();
+    if failed > 0 {
+        let total: usize = results.iter().map(|r| r.rules.len()).sum();
+        bail!(
+            "budget check failed: {} of {} rules violated",
+            failed,
+            total
+        );
+    }
+    Ok(())
+}
+
+fn render_budget(result: &budget::BudgetResult) {
+    for rule in &result.rules {
+        let status = if rule.passed { "PASS" } else { "FAIL" };
+        let actual = match rule.worst() {
+            Some(m) => {
+                let group = m
+                    .group
+                    .as_ref()
+                    .map(|g| format!("{}: ", g))
+                    .unwrap_or_default();
+                format!("actual {}{}", group, rule.unit.format(m.actual))
+            }
+            None => "no requests".to_string(),
+        };
+        println!("{}  {}  ({})", status, rule.name, actual);
+        if rule.per.is_some() && !rule.passed {
+            for m in rule.measurements.iter().filter(|m| !m.passed) {
+                println!(
+                    "        {}  {}",
+                    rule.unit.format(m.actual),
+                    m.group.as_deref().unwrap_or_default()
+                );
+            }
+        }
+    }
+    let passed = result.rules.len() - result.failed_rules();
+    println!("{} of {} rules passed", passed, result.rules.len());
+}
+
+fn run_validate(args: ValidateArgs) -> Result<()> {
+    let mut results = Vec::new();
+    for source in input::expand(&args.paths)? {
+        input::for_each_har(&source, |name, reader| {
+            let problems = validate::validate_reader(BufReader::new(reader))
+                .with_context(|| format!("failed to read file: {}", name))?;
+            results.push(validate::Validation::new(name, problems));
+            Ok(())
+        })?;
+    }
+
+    if args.json {
+        let out = serde_json::to_string_pretty(&results)
+            .with_context(|| "failed to serialize JSON output")?;
+        println!("{}", out);
+    } else {
+        for result in &results {
+            if result.valid {
+                println!("{}: ok", result.source);
+                continue;
+            }
+            let count = result.problems.len();
+            let noun = if count == 1 { "problem" } else { "problems" };
+            println!("{}: {} {}", result.source, count, noun);
+            for problem in &result.problems {
+                println!(
+                    "  {}:{}  {}  {}",
+                    problem.line, problem.column, problem.path, problem.message
+                );
+            }
+        }
+    }
+
+    let problems: usize = results.iter().map(|r| r.problems.len()).sum();
+    if args.strict && problems > 0 {
+        bail!("validation failed: {} problems", problems);
+    }
+    Ok(())
+}
+
+/// Streams every HAR in `source` into each of `builders`.
+fn read_source(
+    source: &input::Source,
+    stream_opts: &har::StreamOptions,
+    builders: &mut [report::ReportBuilder],
+) -> Result<()> {
+    read_entries(source, stream_opts, builders, |_, _| {})
+}
+
+/// Like [`read_source`], also handing every entry and the name of its HAR to `on_entry`.
+fn read_entries(
+    source: &input::Source,
+    stream_opts: &har::StreamOptions,
+    builders: &mut [report::ReportBuilder],
+    mut on_entry: impl FnMut(&str, &har::HarEntry),
+) -> Result<()> {
+    input::for_each_har(source, |name, reader| {
+        for builder in builders.iter_mut() {
+            builder.set_source(name);
+        }
+        let streamed = har::stream_har(BufReader::new(reader), stream_opts, |entry| {
+            for builder in builders.iter_mut() {
+                builder.push(&entry);
+            }
+            on_entry(name, &entry);
+            Ok(())
+        })?;
+        for skipped in streamed.skipped {
+            for builder in builders.iter_mut() {
+                builder.skip(skipped.clone());
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Inputs reported together: all of them when merging, otherwise one file at a time.
+fn input_groups(sources: &[input::Source], merge: bool) -> Vec<&[input::Source]> {
+    if merge || sources.len() == 1 {
+        vec![sources]
+    } else {
+        sources.chunks(1).collect()
+    }
+}
+
+fn parse_percentile(s: &str) -> Result<f64, String> {
+    let p: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid percentile: {}", s))?;
+    if p > 0.0 && p <= 100.0 {
+        Ok(p)
+    } else {
+        Err(format!("percentile must be in (0, 100]: {}", s))
+    }
 }
 
 fn render_text(report: &report::Report) {
     println!("entries: {}", report.entries);
+    if !report.filters.is_empty() {
+        println!("excluded_entries: {}", report.excluded_entries);
+    }
     println!("total_time_ms: {:.2}", report.total_time_ms);
     println!("total_bytes: {}", report::format_bytes(report.total_bytes));
+    if let Some(share) = &report.third_party {
+        println!(
+            "third_party: {} req ({:.1}%), {:.2} ms ({:.1}%), {} ({:.1}%)",
+            share.requests,
+            share.request_share * 100.0,
+            share.time_ms,
+            share.time_share * 100.0,
+            report::format_bytes(share.bytes),
+            share.bytes_share * 100.0
+        );
+    }
 
     println!("\nslowest {}:", report.top_returned);
     for row in &report.top_slowest {
-        println!("{:>8.2} ms {}", row.time_ms, row.url);
+        println!(
+            "{:>8.2} ms {}{}",
+            row.time_ms,
+            row.url,
+            row_tags(report, row)
+        );
     }
 
     println!("\nlargest {} by bytes:", report.top_returned);
     for row in &report.top_largest {
-        println!("{:>10}  {}", report::format_bytes(row.bytes), row.url);
+        println!(
+            "{:>10}  {}{}",
+            report::format_bytes(row.bytes),
+            row.url,
+            row_tags(report, row)
+        );
     }
 
-    if report.group_by.is_some() {
-        println!("\ngroups by host (top {}):", report.top_groups.len());
+    match report.sort_by {
+        Some(report::SortBy::Ttfb) => {
+            println!("\nslowest {} by ttfb:", report.top_sorted.len());
+            for row in &report.top_sorted {
+                println!(
+                    "{:>8.2} ms {}{}",
+                    row.ttfb_ms,
+                    row.url,
+                    row_tags(report, row)
+                );
+            }
+        }
+        Some(report::SortBy::Throughput) => {
+            println!("\nlowest {} by throughput:", report.top_sorted.len());
+            for row in &report.top_sorted {
+                let bps = row.throughput_bps.unwrap_or_default() as u64;
+                println!(
+                    "{:>12}  {}{}",
+                    format!("{}/s", report::format_bytes(bps)),
+                    row.url,
+                    row_tags(report, row)
+                );
+            }
+        }
+        None => {}
+    }
+
+    if let Some(group_by) = &report.group_by {
+        println!(
+            "\ngroups by {} (top {}):",
+            group_by.label(),
+            report.top_groups.len()
+        );
         for group in &report.top_groups {
+            let percentiles: String = group
+                .percentiles
+                .0
+                .iter()
+                .map(|(p, value)| format!("{:>8.2} ms {}  ", value, report::PercentileMap::key(*p)))
+                .collect();
             println!(
-                "{:>4} req  {:>8.2} ms total  {:>8.2} ms avg  {:>8.2} ms p95  {:>10}  {}",
+                "{:>4} req  {:>8.2} ms total  {:>8.2} ms avg  {}{:>10}  {}{}",
                 group.count,
                 group.total_time_ms,
                 group.avg_time_ms,
-                group.p95_time_ms,
+                percentiles,
                 report::format_bytes(group.total_bytes),
-                group.key
+                group.key,
+                party_tag(group.party)
             );
+            println!(
+                "{:>6}time ms: min {:.2}  median {:.2}  max {:.2}  stddev {:.2}",
+                "",
+                group.min_time_ms,
+                group.median_time_ms,
+                group.max_time_ms,
+                group.stddev_time_ms
+            );
+            println!("{:>6}{}", "", format_phase_avgs(&group.timing_phases));
         }
     }
+
+    println!("\ntiming phases:");
+    println!(
+        "  {:<8} {:>6} {:>12} {:>10} {:>10} {:>10}",
+        "phase", "count", "total ms", "avg ms", "p50 ms", "p95 ms"
+    );
+    for stats in &report.timing_phases {
+        if stats.count == 0 {
+            println!("  {:<8} {:>6} {:>12}", stats.phase.as_str(), 0, "n/a");
+            continue;
+        }
+        println!(
+            "  {:<8} {:>6} {:>12.2} {:>10.2} {:>10.2} {:>10.2}",
+            stats.phase.as_str(),
+            stats.count,
+            stats.total_ms,
+            stats.avg_ms,
+            stats.p50_ms,
+            stats.p95_ms
+        );
+    }
+
+    render_url_errors(report);
+    render_skipped_entries(report);
+}
+
+fn render_skipped_entries(report: &report::Report) {
+    if report.skipped_entries.is_empty() {
+        return;
+    }
+
+    println!("\nskipped entries ({}):", report.skipped_entries.len());
+    for skipped in &report.skipped_entries {
+        let source = match &skipped.source {
+            Some(source) if report.sources.len() > 1 => format!("{} ", source),
+            _ => String::new(),
+        };
+        println!("  {}entry {:<5} {}", source, skipped.index, skipped.reason);
+    }
+}
+
+fn render_url_errors(report: &report::Report) {
+    if report.url_errors.is_empty() {
+        return;
+    }
+
+    println!("\ninvalid urls ({}):", report.url_errors.len());
+    for diagnostic in &report.url_errors {
+        println!(
+            "{:>4}x  entry {:<5} {}: {}",
+            diagnostic.count, diagnostic.first_entry, diagnostic.message, diagnostic.url
+        );
+    }
+}
+
+fn party_tag(party: Option<report::Party>) -> String {
+    party
+        .map(|p| format!("  [{}]", p.as_str()))
+        .unwrap_or_default()
+}
+
+/// Party tag plus, when several files were merged, the file the row came from.
+fn row_tags(report: &report::Report, row: &report::ReportRow) -> String {
+    let mut tags = party_tag(row.party);
+    if report.sources.len() > 1 {
+        if let Some(source) = &row.source {
+            tags.push_str(&format!("  ({})", source));
+        }
+    }
+    tags
+}
+
+fn format_phase_avgs(phases: &[report::PhaseStats]) -> String {
+    let parts: Vec<String> = phases
+        .iter()
+        .map(|stats| {
+            if stats.count == 0 {
+                format!("{} -", stats.phase.as_str())
+            } else {
+                format!("{} {:.2}", stats.phase.as_str(), stats.avg_ms)
+            }
+        })
+        .collect();
+    format!("avg ms: {}", parts.join("  "))
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    match args.command {
+        Some(Command::Validate(validate_args)) => return run_validate(validate_args),
+        Some(Command::Check(check_args)) => return run_check(check_args),
+        Some(Command::Diff(diff_args)) => return run_diff(diff_args),
+        Some(Command::Compare(compare_args)) => return run_compare(compare_args),
+        Some(Command::Suite(suite_args)) => return run_suite(suite_args),
+        Some(Command::Record(record_args)) => return run_record(record_args),
+        Some(Command::Trend(trend_args)) => return run_trend(trend_args),
+        None => {}
+    }
 
-    let bytes = fs::read(&args.path)
-        .with_context(|| format!("failed to read file: {}", args.path.display()))?;
+    let format = if args.json {
+        OutputFormat::Json
+    } else {
+        args.format
+    };
+    let templates = match &args.endpoint_templates {
+        Some(path) => endpoint::load_templates(path)?,
+        None => Vec::new(),
+    };
+    let opts = report::ReportOptions {
+        top: args.top,
+        group_by: args.group_by,
+        all_groups: false,
+        sort_by: args.sort_by,
+        percentiles: args.percentiles,
+        percentile_method: args.percentile_method,
+        first_party: args.first_party,
+        endpoints: endpoint::EndpointRules {
+            with_method: args.endpoint_method,
+            templates,
+        },
+        filters: filter::Filters {
+            hosts: args.host,
+            url_regex: args.url_regex,
+            exclude_url_regex: args.exclude_url_regex,
+            methods: args.method,
+            statuses: args.status,
+            mime_types: args.mime,
+            min_time_ms: args.min_time,
+            min_bytes: args.min_bytes,
+            pages: args.page,
+            since_ms: args.since,
+            until_ms: args.until,
+            where_expr: args.where_expr,
+        },
+        waterfall: if format == OutputFormat::Html {
+            html::WATERFALL_ROWS
+        } else {
+            0
+        },
+    };
 
-    let har = har::parse_har(&bytes)?;
-    let report = report::build_report(&har.log.entries, args.top, args.group_by);
+    let sources = input::expand(&args.paths)?;
+    let stream_opts = har::StreamOptions {
+        keep_content_text: args.keep_content_text,
+        lenient: args.lenient,
+    };
+    let mut reports = Vec::new();
+    for group in input_groups(&sources, args.merge) {
+        let mut builder = report::ReportBuilder::new(opts.clone());
+        for source in group {
+            read_source(source, &stream_opts, std::slice::from_mut(&mut builder))?;
+        }
+        reports.push(builder.finish());
+    }
 
-    if args.json {
-        let out = serde_json::to_string_pretty(&report)
+    match format {
+        OutputFormat::Json => {
+            let out = match reports.as_slice() {
+                [report] => serde_json::to_string_pretty(report),
+                reports => serde_json::to_string_pretty(reports),
+            }
             .with_context(|| "failed to serialize JSON output")?;
-        println!("{}", out);
-    } else {
-        render_text(&report);
+            println!("{}", out);
+        }
+        OutputFormat::Html => print!("{}", html::render(&reports)),
+        OutputFormat::Markdown => print!("{}", markdown::render(&reports)),
+        OutputFormat::Text => {
+            for (index, report) in reports.iter().enumerate() {
+                if reports.len() > 1 {
+                    if index > 0 {
+                        println!();
+                    }
+                    println!("== {} ==", report.sources.join(", "));
+                }
+                render_text(report);
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,337 @@
+use std::fmt::Write;
+
+use crate::budget::BudgetResult;
+use crate::report::{format_bytes, GroupRow, PercentileMap, Report, ReportRow, SortBy};
+
+/// Renders reports as GitHub-flavored Markdown for pull request comments: a summary table per
+/// report with the request lists and groups folded into `<details>` sections.
+pub fn render(reports: &[Report]) -> String {
+    let mut out = String::new();
+    for (index, report) in reports.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        render_report(&mut out, report);
+    }
+    out
+}
+
+fn render_report(out: &mut String, report: &Report) {
+    let _ = writeln!(
+        out,
+        "### perf_tool report: {}\n",
+        text(&report.sources.join(", "))
+    );
+    out.push_str("| metric | value |\n|---|---:|\n");
+    let mut row = |metric: &str, value: String| {
+        let _ = writeln!(out, "| {} | {} |", metric, text(&value));
+    };
+    row("entries", report.entries.to_string());
+    if !report.filters.is_empty() {
+        row("excluded entries", report.excluded_entries.to_string());
+    }
+    row("total time", format_ms(report.total_time_ms));
+    row("total bytes", format_bytes(report.total_bytes));
+    for (p, value) in &report.time_percentiles.0 {
+        row(&PercentileMap::key(*p), format_ms(*value));
+    }
+    row("max time", format_ms(report.max_time_ms));
+    let statuses: Vec<String> = report
+        .status_classes
+        .iter()
+        .map(|(class, count)| format!("{} {}", class, count))
+        .collect();
+    row("statuses", statuses.join(", "));
+    if let Some(share) = &report.third_party {
+        row(
+            "third-party requests",
+            format!("{} ({:.1}%)", share.requests, share.request_share * 100.0),
+        );
+        row(
+            "third-party time",
+            format!(
+                "{} ({:.1}%)",
+                format_ms(share.time_ms),
+                share.time_share * 100.0
+            ),
+        );
+        row(
+            "third-party bytes",
+            format!(
+                "{} ({:.1}%)",
+                format_bytes(share.bytes),
+                share.bytes_share * 100.0
+            ),
+        );
+    }
+
+    render_rows(out, "Slowest requests", &report.top_slowest);
+    render_rows(out, "Largest requests", &report.top_largest);
+    match report.sort_by {
+        Some(SortBy::Ttfb) => render_rows(out, "Slowest by TTFB", &report.top_sorted),
+        Some(SortBy::Throughput) => render_rows(out, "Lowest throughput", &report.top_sorted),
+        None => {}
+    }
+    if let Some(group_by) = &report.group_by {
+        render_groups(out, &group_by.label(), &report.top_groups);
+    }
+}
+
+fn render_rows(out: &mut String, title: &str, rows: &[ReportRow]) {
+    if rows.is_empty() {
+        return;
+    }
+    open_details(out, &format!("{} ({})", title, rows.len()));
+    out.push_str("| # | URL | time | bytes | TTFB |\n|---:|---|---:|---:|---:|\n");
+    for (index, row) in rows.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            index + 1,
+            code(&row.url),
+            format_ms(row.time_ms),
+            format_bytes(row.bytes),
+            format_ms(row.ttfb_ms)
+        );
+    }
+    close_details(out);
+}
+
+fn render_groups(out: &mut String, label: &str, groups: &[GroupRow]) {
+    if groups.is_empty() {
+        return;
+    }
+    open_details(out, &format!("Groups by {} ({})", label, groups.len()));
+    out.push_str("| group | requests | total | avg |");
+    for (p, _) in &groups[0].percentiles.0 {
+        let _ = write!(out, " {} |", PercentileMap::key(*p));
+    }
+    out.push_str(" bytes |\n|---|---:|---:|---:|");
+    for _ in &groups[0].percentiles.0 {
+        out.push_str("---:|");
+    }
+    out.push_str("---:|\n");
+    for group in groups {
+        let _ = write!(
+            out,
+            "| {} | {} | {} | {} |",
+            code(&group.key),
+            group.count,
+            format_ms(group.total_time_ms),
+            format_ms(group.avg_time_ms)
+        );
+        for (_, value) in &group.percentiles.0 {
+            let _ = write!(out, " {} |", format_ms(*value));
+        }
+        let _ = writeln!(out, " {} |", format_bytes(group.total_bytes));
+    }
+    close_details(out);
+}
+
+/// Renders budget results: one table of rules per checked input, with every violating
+/// measurement folded into a `<details>` section.
+pub fn render_budget(results: &[BudgetResult]) -> String {
+    let failed: usize = results.iter().map(|r| r.failed_rules()).sum();
+    let total: usize = results.iter().map(|r| r.rules.len()).sum();
+    let mut out = if failed == 0 {
+        format!("### Performance budget: passed ({} rules)\n", total)
+    } else {
+        format!(
+            "### Performance budget: failed ({} of {} rules violated)\n",
+            failed, total
+        )
+    };
+
+    for result in results {
+        let _ = writeln!(out, "\n**{}**\n", text(&result.sources.join(", ")));
+        out.push_str("| | rule | actual | limit |\n|---|---|---:|---:|\n");
+        for rule in &result.rules {
+            let status = if rule.passed { "PASS" } else { "**FAIL**" };
+            let actual = match rule.worst() {
+                Some(m) => {
+                    let group = m.group.as_ref().map(|g| format!("{}: ", code(g)));
+                    format!(
+                        "{}{}",
+                        group.unwrap_or_default(),
+                        rule.unit.format(m.actual)
+                    )
+                }
+                None => "no requests".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                status,
+                text(&rule.name),
+                actual,
+                rule.unit.format(rule.max)
+            );
+        }
+
+        let mut violations: Vec<(&str, Option<&str>, String, String)> = Vec::new();
+        for rule in result.rules.iter().filter(|r| !r.passed) {
+            let mut failed: Vec<_> = rule.measurements.iter().filter(|m| !m.passed).collect();
+            failed.sort_by(|a, b| b.actual.total_cmp(&a.actual));
+            for m in failed {
+                violations.push((
+                    &rule.name,
+                    m.group.as_deref(),
+                    rule.unit.format(m.actual),
+                    rule.unit.format(rule.max),
+                ));
+            }
+        }
+        if violations.is_empty() {
+            continue;
+        }
+        open_details(&mut out, &format!("Violations ({})", violations.len()));
+        out.push_str("| rule | group | actual | limit |\n|---|---|---:|---:|\n");
+        for (rule, group, actual, max) in violations {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                text(rule),
+                group.map(code).unwrap_or_default(),
+                actual,
+                max
+            );
+        }
+        close_details(&mut out);
+    }
+    out
+}
+
+fn open_details(out: &mut String, summary: &str) {
+    let _ = writeln!(
+        out,
+        "\n<details>\n<summary>{}</summary>\n",
+        html_escape(summary)
+    );
+}
+
+fn close_details(out: &mut String) {
+    out.push_str("\n</details>\n");
+}
+
+fn format_ms(ms: f64) -> String {
+    format!("{:.2} ms", ms)
+}
+
+/// Plain text for a table cell, with Markdown and HTML punctuation escaped.
+fn text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '~' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A code span for URLs and group keys; pipes still need escaping inside table cells.
+fn code(s: &str) -> String {
+    let s = s.replace(['\n', '\r'], " ").replace('|', "\\|");
+    if s.contains('`') {
+        format!("`` {} ``", s)
+    } else {
+        format!("`{}`", s)
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::{Measurement, RuleResult, Unit};
+    use crate::har::{HarEntry, HarRequest};
+    use crate::report::{build_report, GroupBy, GroupSpec, ReportOptions};
+
+    #[test]
+    fn renders_report_tables_in_details() {
+        let entries: Vec<HarEntry> = [("https://a.test/x|y", 120.0), ("https://b.test/", 80.0)]
+            .iter()
+            .map(|(url, time)| HarEntry {
+                time: *time,
+                request: HarRequest {
+                    url: url.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect();
+        let opts = ReportOptions {
+            group_by: Some(GroupSpec(vec![GroupBy::Host])),
+            ..Default::default()
+        };
+        let md = render(&[build_report(&entries, &opts)]);
+
+        assert!(md.starts_with("### perf_tool report: \n\n| metric | value |\n|---|---:|\n"));
+        assert!(md.contains("| total time | 200.00 ms |\n"));
+        assert!(md.contains("| p95 | 120.00 ms |\n"));
+        assert!(md.contains("<details>\n<summary>Slowest requests (2)</summary>\n\n"));
+        assert!(md.contains("| 1 | `https://a.test/x\\|y` | 120.00 ms | 0 B | 0.00 ms |\n"));
+        assert!(md.contains(
+            "| group | requests | total | avg | p95 | bytes |\n|---|---:|---:|---:|---:|---:|\n"
+        ));
+        assert!(md.contains("| `a.test` | 1 | 120.00 ms | 120.00 ms | 120.00 ms | 0 B |\n"));
+        assert_eq!(
+            md.matches("<details>").count(),
+            md.matches("</details>").count()
+        );
+    }
+
+    #[test]
+    fn renders_budget_results_with_violations() {
+        let measurement = |group: &str, actual: f64| Measurement {
+            group: Some(group.to_string()),
+            actual,
+            passed: actual <= 100.0,
+        };
+        let result = BudgetResult {
+            sources: vec!["run.har".to_string()],
+            passed: false,
+            rules: vec![RuleResult {
+                name: "p95_time per host <= 100.00 ms".to_string(),
+                metric: "p95_time".to_string(),
+                per: Some(GroupBy::Host),
+                unit: Unit::Ms,
+                max: 100.0,
+                passed: false,
+                measurements: vec![
+                    measurement("a.test", 150.0),
+                    measurement("b.test", 50.0),
+                    measurement("c.test", 300.0),
+                ],
+            }],
+        };
+
+        assert_eq!(
+            render_budget(&[result]),
+            "### Performance budget: failed (1 of 1 rules violated)\n\
+             \n**run.har**\n\n\
+             | | rule | actual | limit |\n|---|---|---:|---:|\n\
+             | **FAIL** | p95\\_time per host \\<= 100.00 ms | `c.test`: 300.00 ms | 100.00 ms |\n\
+             \n<details>\n<summary>Violations (2)</summary>\n\n\
+             | rule | group | actual | limit |\n|---|---|---:|---:|\n\
+             | p95\\_time per host \\<= 100.00 ms | `c.test` | 300.00 ms | 100.00 ms |\n\
+             | p95\\_time per host \\<= 100.00 ms | `a.test` | 150.00 ms | 100.00 ms |\n\
+             \n</details>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_cells() {
+        assert_eq!(text("a|b *c* <d>"), "a\\|b \\*c\\* \\<d\\>");
+        assert_eq!(code("a`b|c"), "`` a`b\\|c ``");
+    }
+}
@@ -0,0 +1,158 @@
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use thiserror::Error;
+use url::Url;
+
+/// Why a request URL could not be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Error)]
+#[serde(rename_all = "kebab-case")]
+pub enum UrlError {
+    #[error("empty URL")]
+    Empty,
+    #[error("relative URL without a base")]
+    Relative,
+    #[error("missing host")]
+    MissingHost,
+    #[error("invalid host")]
+    InvalidHost,
+    #[error("invalid port")]
+    InvalidPort,
+    #[error("malformed URL")]
+    Malformed,
+}
+
+impl From<url::ParseError> for UrlError {
+    fn from(err: url::ParseError) -> Self {
+        match err {
+            url::ParseError::EmptyHost => UrlError::MissingHost,
+            url::ParseError::IdnaError
+            | url::ParseError::InvalidIpv4Address
+            | url::ParseError::InvalidIpv6Address
+            | url::ParseError::InvalidDomainCharacter => UrlError::InvalidHost,
+            url::ParseError::InvalidPort => UrlError::InvalidPort,
+            url::ParseError::RelativeUrlWithoutBase
+            | url::ParseError::RelativeUrlWithCannotBeABaseBase => UrlError::Relative,
+            _ => UrlError::Malformed,
+        }
+    }
+}
+
+/// A parsed request URL shared by grouping, classification and matching code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedUrl {
+    /// Lowercased scheme.
+    pub scheme: String,
+    /// Lowercased ASCII host (IDNs in punycode, IPv6 in brackets); `None` for `data:` and friends.
+    pub host: Option<String>,
+    /// Explicit port, `None` when absent or equal to the scheme's default.
+    pub port: Option<u16>,
+    /// Path as encoded in the URL, `/` for hierarchical URLs with an empty path. Endpoint
+    /// templates work on its segments.
+    pub raw_path: String,
+    /// `raw_path` with each segment decoded by [`decode_segment`], for display and matching.
+    pub path: String,
+    /// Decoded query string pairs in order.
+    pub query: Vec<(String, String)>,
+    pub fragment: Option<String>,
+}
+
+impl ParsedUrl {
+    pub fn parse(raw: &str) -> Result<Self, UrlError> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err(UrlError::Empty);
+        }
+
+        let url = Url::parse(raw)?;
+        if url.has_authority() && url.host_str().is_none_or(str::is_empty) {
+            return Err(UrlError::MissingHost);
+        }
+
+        let raw_path = match url.path() {
+            "" if url.has_authority() => "/".to_string(),
+            other => other.to_string(),
+        };
+        let segments: Vec<String> = raw_path.split('/').map(decode_segment).collect();
+
+        Ok(Self {
+            scheme: url.scheme().to_string(),
+            host: url.host_str().map(str::to_ascii_lowercase),
+            port: url.port(),
+            path: segments.join("/"),
+            raw_path,
+            query: url.query_pairs().into_owned().collect(),
+            fragment: url.fragment().map(str::to_string),
+        })
+    }
+}
+
+/// Percent-decodes one path segment. An encoded `/` stays `%2F`, so decoding never changes
+/// the number of segments.
+pub fn decode_segment(segment: &str) -> String {
+    percent_decode_str(segment)
+        .decode_utf8_lossy()
+        .replace('/', "%2F")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_components() {
+        let url = ParsedUrl::parse("HTTPS://User@API.Example.com:8443/a%20b/c?x=1&y=%C3%A9#frag")
+            .expect("valid URL");
+        assert_eq!(url.scheme, "https");
+        assert_eq!(url.host.as_deref(), Some("api.example.com"));
+        assert_eq!(url.port, Some(8443));
+        assert_eq!(url.path, "/a b/c");
+        assert_eq!(
+            url.query,
+            vec![
+                ("x".to_string(), "1".to_string()),
+                ("y".to_string(), "é".to_string())
+            ]
+        );
+        assert_eq!(url.fragment.as_deref(), Some("frag"));
+    }
+
+    #[test]
+    fn decoding_keeps_encoded_slashes_inside_segments() {
+        let url = ParsedUrl::parse("https://a.com/files/a%2Fb/caf%C3%A9").expect("valid URL");
+        assert_eq!(url.raw_path, "/files/a%2Fb/caf%C3%A9");
+        assert_eq!(url.path, "/files/a%2Fb/café");
+    }
+
+    #[test]
+    fn normalizes_default_ports_query_without_path_and_idns() {
+        let url = ParsedUrl::parse("https://a.com:443?x=1").expect("valid URL");
+        assert_eq!(url.host.as_deref(), Some("a.com"));
+        assert_eq!(url.port, None);
+        assert_eq!(url.path, "/");
+
+        let idn = ParsedUrl::parse("https://Bücher.example/").expect("valid URL");
+        assert_eq!(idn.host.as_deref(), Some("xn--bcher-kva.example"));
+
+        let ipv6 = ParsedUrl::parse("http://[::1]:8080/").expect("valid URL");
+        assert_eq!(ipv6.host.as_deref(), Some("[::1]"));
+    }
+
+    #[test]
+    fn urls_without_host_are_valid() {
+        let url = ParsedUrl::parse("data:image/png;base64,iVBORw0KGgo=").expect("valid URL");
+        assert_eq!(url.scheme, "data");
+        assert_eq!(url.host, None);
+    }
+
+    #[test]
+    fn reports_structured_errors() {
+        assert_eq!(ParsedUrl::parse(""), Err(UrlError::Empty));
+        assert_eq!(ParsedUrl::parse("not a url"), Err(UrlError::Relative));
+        assert_eq!(ParsedUrl::parse("https://"), Err(UrlError::MissingHost));
+        assert_eq!(
+            ParsedUrl::parse("https://a.com:99999/"),
+            Err(UrlError::InvalidPort)
+        );
+        assert_eq!(ParsedUrl::parse("http://[::1/"), Err(UrlError::InvalidHost));
+    }
+}
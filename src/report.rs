@@ -1,27 +1,285 @@
-use std::collections::HashMap;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 
+use chrono::{DateTime, FixedOffset};
 use clap::ValueEnum;
-use serde::Serialize;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
 
-use crate::har::HarEntry;
+use crate::domain;
+use crate::endpoint::EndpointRules;
+use crate::filter::{self, Filters};
+use crate::har::{HarEntry, HarTimings, SkippedEntry};
+use crate::parsed_url::{ParsedUrl, UrlError};
+use crate::sketch::QuantileSketch;
 
-#[derive(Debug, Clone, Copy, Serialize, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
 #[serde(rename_all = "kebab-case")]
 pub enum GroupBy {
+    /// Request host, lowercased and without port.
     Host,
+    /// Registrable domain (eTLD+1) of the request host.
+    Domain,
+    /// Request method (GET, POST, ...).
+    Method,
+    /// Exact response status code.
+    Status,
+    /// Response status class (2xx, 3xx, ...).
+    StatusClass,
+    /// Response MIME type without parameters.
+    MimeType,
+    /// Browser resource type, derived from the MIME type when not exported.
+    ResourceType,
+    /// URL scheme.
+    Scheme,
+    /// Response HTTP version.
+    HttpVersion,
+    /// URL path without query string.
+    Path,
+    /// URL path with IDs, UUIDs and hashes replaced by placeholders (`/users/{id}`).
+    Endpoint,
+    /// Page the request belongs to (`pageref`).
+    Page,
+    /// Server IP address.
+    ServerIp,
+}
+
+impl GroupBy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupBy::Host => "host",
+            GroupBy::Domain => "domain",
+            GroupBy::Method => "method",
+            GroupBy::Status => "status",
+            GroupBy::StatusClass => "status-class",
+            GroupBy::MimeType => "mime-type",
+            GroupBy::ResourceType => "resource-type",
+            GroupBy::Scheme => "scheme",
+            GroupBy::HttpVersion => "http-version",
+            GroupBy::Path => "path",
+            GroupBy::Endpoint => "endpoint",
+            GroupBy::Page => "page",
+            GroupBy::ServerIp => "server-ip",
+        }
+    }
+
+    pub fn key(self, entry: &HarEntry, opts: &ReportOptions) -> String {
+        match self {
+            GroupBy::Host => host_key(&entry.request.url),
+            GroupBy::Domain => domain_key(&entry.request.url),
+            GroupBy::Method => method_key(entry),
+            GroupBy::Status => status_key(entry),
+            GroupBy::StatusClass => status_class_key(entry),
+            GroupBy::MimeType => mime_type_key(entry),
+            GroupBy::ResourceType => resource_type_key(entry),
+            GroupBy::Scheme => scheme_key(&entry.request.url),
+            GroupBy::HttpVersion => http_version_key(entry),
+            GroupBy::Path => path_key(&entry.request.url),
+            GroupBy::Endpoint => endpoint_key(entry, &opts.endpoints),
+            GroupBy::Page => page_key(entry),
+            GroupBy::ServerIp => server_ip_key(entry),
+        }
+    }
+}
+
+/// One or more grouping dimensions; serialized as a comma-separated list such as `host,status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSpec(pub Vec<GroupBy>);
+
+impl GroupSpec {
+    pub fn label(&self) -> String {
+        let names: Vec<&str> = self.0.iter().map(|g| g.as_str()).collect();
+        names.join(",")
+    }
+
+    pub fn keys(&self, entry: &HarEntry, opts: &ReportOptions) -> Vec<String> {
+        self.0.iter().map(|g| g.key(entry, opts)).collect()
+    }
+}
+
+/// Parses `--group-by`, a comma-separated list of distinct dimensions such as `host,status`.
+pub fn parse_group_spec(s: &str) -> Result<GroupSpec, String> {
+    let mut dimensions = Vec::new();
+    for name in s.split(',') {
+        let group_by = <GroupBy as ValueEnum>::from_str(name.trim(), false).map_err(|_| {
+            let names: Vec<&str> = GroupBy::value_variants()
+                .iter()
+                .map(|g| g.as_str())
+                .collect();
+            format!(
+                "invalid group-by dimension: {} (expected one of {})",
+                name,
+                names.join(", ")
+            )
+        })?;
+        if dimensions.contains(&group_by) {
+            return Err(format!(
+                "group-by dimension given twice: {}",
+                group_by.as_str()
+            ));
+        }
+        dimensions.push(group_by);
+    }
+    Ok(GroupSpec(dimensions))
+}
+
+impl From<GroupBy> for GroupSpec {
+    fn from(group_by: GroupBy) -> Self {
+        GroupSpec(vec![group_by])
+    }
+}
+
+impl Serialize for GroupSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.label())
+    }
+}
+
+/// Per-dimension values of a group key, serialized as an object in grouping order.
+#[derive(Debug, Default)]
+pub struct GroupDimensions(pub Vec<(GroupBy, String)>);
+
+impl Serialize for GroupDimensions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (group_by, value) in &self.0 {
+            map.serialize_entry(group_by.as_str(), value)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Party {
+    #[serde(rename = "first-party")]
+    First,
+    #[serde(rename = "third-party")]
+    Third,
+    /// A group containing both first- and third-party requests.
+    Mixed,
+}
+
+impl Party {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Party::First => "first-party",
+            Party::Third => "third-party",
+            Party::Mixed => "mixed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortBy {
+    /// Longest time to first byte first.
+    Ttfb,
+    /// Lowest download throughput first.
+    Throughput,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PercentileMethod {
+    /// Smallest sample with at least p% of values at or below it.
+    #[default]
+    NearestRank,
+    /// Linear interpolation between the two closest ranks.
+    Linear,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub top: usize,
+    pub group_by: Option<GroupSpec>,
+    /// Keep every group instead of only the `top` slowest.
+    pub all_groups: bool,
+    pub sort_by: Option<SortBy>,
+    /// Percentiles (0-100] reported for each group.
+    pub percentiles: Vec<f64>,
+    pub percentile_method: PercentileMethod,
+    /// Domains (and their subdomains) counted as first-party; empty disables classification.
+    pub first_party: Vec<String>,
+    pub endpoints: EndpointRules,
+    /// Entries not matching these filters are left out of every statistic.
+    pub filters: Filters,
+    /// Requests kept in file order for a waterfall chart; 0 keeps none.
+    pub waterfall: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            top: 10,
+            group_by: None,
+            all_groups: false,
+            sort_by: None,
+            percentiles: vec![95.0],
+            percentile_method: PercentileMethod::NearestRank,
+            first_party: Vec::new(),
+            endpoints: EndpointRules::default(),
+            filters: Filters::default(),
+            waterfall: 0,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct Report {
+    /// Input files that contributed entries, in the order they were read.
+    pub sources: Vec<String>,
+    /// Filters the entries had to match, as given on the command line.
+    pub filters: Filters,
+    /// Entries left out by `filters`.
+    pub excluded_entries: usize,
     pub entries: usize,
     pub total_time_ms: f64,
     pub total_bytes: u64,
+    /// Time of the slowest request.
+    pub max_time_ms: f64,
+    /// Request time percentiles over all entries, as configured for groups.
+    pub time_percentiles: PercentileMap,
+    /// Responses per status class (`2xx`, `5xx`, `<no-status>`).
+    pub status_classes: BTreeMap<String, usize>,
     pub top_requested: usize,
     pub top_returned: usize,
-    pub group_by: Option<GroupBy>,
+    pub group_by: Option<GroupSpec>,
+    pub sort_by: Option<SortBy>,
+    pub percentile_method: PercentileMethod,
     pub top_slowest: Vec<ReportRow>,
     pub top_largest: Vec<ReportRow>,
+    pub top_sorted: Vec<ReportRow>,
     pub top_groups: Vec<GroupRow>,
+    pub timing_phases: Vec<PhaseStats>,
+    pub third_party: Option<ThirdPartyShare>,
+    pub url_errors: Vec<UrlDiagnostic>,
+    /// Entries dropped by lenient parsing; always empty otherwise.
+    pub skipped_entries: Vec<SkippedEntry>,
+    /// The first `waterfall` requests with a start time, in file order.
+    pub waterfall: Vec<WaterfallRow>,
+}
+
+/// A distinct request URL that failed to parse, with how often it occurred.
+#[derive(Debug, Serialize)]
+pub struct UrlDiagnostic {
+    pub url: String,
+    pub error: UrlError,
+    pub message: String,
+    pub count: usize,
+    /// Index of the first entry with this URL.
+    pub first_entry: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThirdPartyShare {
+    pub requests: usize,
+    pub time_ms: f64,
+    pub bytes: u64,
+    /// Fractions (0-1) of the whole file.
+    pub request_share: f64,
+    pub time_share: f64,
+    pub bytes_share: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,24 +287,291 @@ pub struct ReportRow {
     pub url: String,
     pub time_ms: f64,
     pub bytes: u64,
+    pub ttfb_ms: f64,
+    pub download_ms: f64,
+    pub throughput_bps: Option<f64>,
+    pub party: Option<Party>,
+    /// Input file the entry was read from.
+    pub source: Option<String>,
+}
+
+/// A request placed on its file's timeline.
+#[derive(Debug, Serialize)]
+pub struct WaterfallRow {
+    pub method: String,
+    pub url: String,
+    pub status: i64,
+    /// Start relative to the first `startedDateTime` of the same file's entries.
+    pub start_ms: f64,
+    pub time_ms: f64,
+    /// Applicable timing phases in HAR order.
+    pub phases: Vec<PhaseSpan>,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhaseSpan {
+    pub phase: TimingPhase,
+    pub ms: f64,
 }
 
 #[derive(Debug, Serialize)]
 pub struct GroupRow {
+    /// Dimension values joined with `" | "`.
     pub key: String,
+    pub dimensions: GroupDimensions,
     pub count: usize,
     pub total_time_ms: f64,
     pub avg_time_ms: f64,
     pub p95_time_ms: f64,
+    pub min_time_ms: f64,
+    pub max_time_ms: f64,
+    pub median_time_ms: f64,
+    /// Population standard deviation of request times.
+    pub stddev_time_ms: f64,
+    pub percentiles: PercentileMap,
     pub total_bytes: u64,
+    pub party: Option<Party>,
+    pub timing_phases: Vec<PhaseStats>,
+}
+
+/// Percentile values keyed as `p50`, `p99.9`, ... in the order they were requested.
+#[derive(Debug, Default)]
+pub struct PercentileMap(pub Vec<(f64, f64)>);
+
+impl PercentileMap {
+    pub fn key(p: f64) -> String {
+        format!("p{}", p)
+    }
+}
+
+impl Serialize for PercentileMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (p, value) in &self.0 {
+            map.serialize_entry(&Self::key(*p), value)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimingPhase {
+    Blocked,
+    Dns,
+    Connect,
+    Ssl,
+    Send,
+    Wait,
+    Receive,
+}
+
+impl TimingPhase {
+    pub const ALL: [TimingPhase; 7] = [
+        TimingPhase::Blocked,
+        TimingPhase::Dns,
+        TimingPhase::Connect,
+        TimingPhase::Ssl,
+        TimingPhase::Send,
+        TimingPhase::Wait,
+        TimingPhase::Receive,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimingPhase::Blocked => "blocked",
+            TimingPhase::Dns => "dns",
+            TimingPhase::Connect => "connect",
+            TimingPhase::Ssl => "ssl",
+            TimingPhase::Send => "send",
+            TimingPhase::Wait => "wait",
+            TimingPhase::Receive => "receive",
+        }
+    }
+
+    /// Returns the phase duration, or `None` when the HAR marks it as not applicable (`-1`).
+    pub fn value(self, timings: &HarTimings) -> Option<f64> {
+        let v = match self {
+            TimingPhase::Blocked => timings.blocked,
+            TimingPhase::Dns => timings.dns,
+            TimingPhase::Connect => timings.connect,
+            TimingPhase::Ssl => timings.ssl,
+            TimingPhase::Send => timings.send,
+            TimingPhase::Wait => timings.wait,
+            TimingPhase::Receive => timings.receive,
+        };
+        (v >= 0.0).then_some(v)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhaseStats {
+    pub phase: TimingPhase,
+    pub count: usize,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+#[derive(Debug, Default)]
+struct PhaseAccumulator {
+    values: [QuantileSketch; 7],
+}
+
+impl PhaseAccumulator {
+    fn push(&mut self, timings: &HarTimings) {
+        for (phase, values) in TimingPhase::ALL.iter().zip(self.values.iter_mut()) {
+            if let Some(v) = phase.value(timings) {
+                values.push(v);
+            }
+        }
+    }
+
+    fn finish(self, method: PercentileMethod) -> Vec<PhaseStats> {
+        TimingPhase::ALL
+            .into_iter()
+            .zip(self.values)
+            .map(|(phase, mut values)| PhaseStats {
+                phase,
+                count: values.count(),
+                total_ms: values.sum(),
+                avg_ms: values.mean(),
+                p50_ms: values.quantile(0.50, method),
+                p95_ms: values.quantile(0.95, method),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Default)]
 struct GroupAccumulator {
-    count: usize,
-    total_time_ms: f64,
     total_bytes: u64,
-    times: Vec<f64>,
+    times: QuantileSketch,
+    phases: PhaseAccumulator,
+    party: Option<Party>,
+}
+
+impl GroupAccumulator {
+    fn finish(mut self, keys: Vec<String>, group_by: &GroupSpec, opts: &ReportOptions) -> GroupRow {
+        let method = opts.percentile_method;
+        let percentiles = opts
+            .percentiles
+            .iter()
+            .map(|p| (*p, self.times.quantile(p / 100.0, method)))
+            .collect();
+        GroupRow {
+            key: keys.join(" | "),
+            dimensions: GroupDimensions(group_by.0.iter().copied().zip(keys).collect()),
+            count: self.times.count(),
+            total_time_ms: self.times.sum(),
+            avg_time_ms: self.times.mean(),
+            p95_time_ms: self.times.quantile(0.95, method),
+            min_time_ms: self.times.min(),
+            max_time_ms: self.times.max(),
+            median_time_ms: self.times.quantile(0.5, method),
+            stddev_time_ms: self.times.stddev(),
+            percentiles: PercentileMap(percentiles),
+            total_bytes: self.total_bytes,
+            party: self.party,
+            timing_phases: self.phases.finish(method),
+        }
+    }
+}
+
+/// Ranking of a row in a top-N list: higher scores first, ties keep the earlier entry.
+#[derive(Debug, Clone, Copy)]
+struct Rank {
+    score: f64,
+    seq: usize,
+}
+
+impl PartialEq for Rank {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Rank {}
+
+impl PartialOrd for Rank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug)]
+struct Ranked<T> {
+    rank: Rank,
+    item: T,
+}
+
+impl<T> PartialEq for Ranked<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank == other.rank
+    }
+}
+
+impl<T> Eq for Ranked<T> {}
+
+impl<T> PartialOrd for Ranked<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Ranked<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank.cmp(&other.rank)
+    }
+}
+
+/// Keeps the `k` best-ranked items seen so far in a min-heap.
+#[derive(Debug)]
+struct TopK<T> {
+    k: usize,
+    heap: BinaryHeap<Reverse<Ranked<T>>>,
+}
+
+impl<T> TopK<T> {
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    /// Offers an item, only building it with `make` when it makes the cut.
+    fn push_with(&mut self, rank: Rank, make: impl FnOnce() -> T) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(Ranked { rank, item: make() }));
+        } else if self
+            .heap
+            .peek()
+            .is_some_and(|Reverse(worst)| rank > worst.rank)
+        {
+            self.heap.pop();
+            self.heap.push(Reverse(Ranked { rank, item: make() }));
+        }
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        // Ascending order of `Reverse` is descending rank.
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(ranked)| ranked.item)
+            .collect()
+    }
 }
 
 pub fn pos_i64_to_u64(x: Option<i64>) -> u64 {
@@ -64,6 +589,56 @@ pub fn entry_bytes(e: &HarEntry) -> u64 {
     body + headers
 }
 
+fn phase_or_zero(v: f64) -> f64 {
+    v.max(0.0)
+}
+
+/// Time to first byte: everything before the response starts arriving.
+pub fn entry_ttfb_ms(e: &HarEntry) -> f64 {
+    let t = &e.timings;
+    phase_or_zero(t.blocked)
+        + phase_or_zero(t.dns)
+        + phase_or_zero(t.connect)
+        + phase_or_zero(t.send)
+        + phase_or_zero(t.wait)
+}
+
+pub fn entry_download_ms(e: &HarEntry) -> f64 {
+    phase_or_zero(e.timings.receive)
+}
+
+/// Effective download throughput in bytes/sec, or `None` when there is no receive time.
+pub fn entry_throughput_bps(e: &HarEntry) -> Option<f64> {
+    let download_ms = entry_download_ms(e);
+    (download_ms > 0.0).then(|| entry_bytes(e) as f64 / (download_ms / 1000.0))
+}
+
+/// Classifies `e` against `first_party`, or `None` when no first-party domains are configured.
+pub fn entry_party(e: &HarEntry, first_party: &[String]) -> Option<Party> {
+    if first_party.is_empty() {
+        return None;
+    }
+
+    if domain::matches_any(&host_key(&e.request.url), first_party) {
+        Some(Party::First)
+    } else {
+        Some(Party::Third)
+    }
+}
+
+fn report_row(e: &HarEntry, opts: &ReportOptions, source: Option<&String>) -> ReportRow {
+    ReportRow {
+        url: e.request.url.clone(),
+        time_ms: e.time,
+        bytes: entry_bytes(e),
+        ttfb_ms: entry_ttfb_ms(e),
+        download_ms: entry_download_ms(e),
+        throughput_bps: entry_throughput_bps(e),
+        party: entry_party(e, &opts.first_party),
+        source: source.cloned(),
+    }
+}
+
 fn nearest_rank_percentile(values: &[f64], p: f64) -> f64 {
     if values.is_empty() {
         return 0.0;
@@ -74,136 +649,412 @@ fn nearest_rank_percentile(values: &[f64], p: f64) -> f64 {
     values[idx]
 }
 
-fn host_key(url: &str) -> String {
-    let Some((_, after_scheme)) = url.split_once("://") else {
-        return "<invalid-host>".to_string();
-    };
+fn linear_percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
 
-    let authority = after_scheme.split('/').next().unwrap_or_default();
-    if authority.is_empty() {
-        return "<invalid-host>".to_string();
+    let h = p.clamp(0.0, 1.0) * (values.len() - 1) as f64;
+    let lo = h.floor() as usize;
+    let hi = h.ceil() as usize;
+    values[lo] + (h - lo as f64) * (values[hi] - values[lo])
+}
+
+/// `values` must be sorted ascending; `p` is a fraction in `[0, 1]`.
+pub fn percentile(values: &[f64], p: f64, method: PercentileMethod) -> f64 {
+    match method {
+        PercentileMethod::NearestRank => nearest_rank_percentile(values, p),
+        PercentileMethod::Linear => linear_percentile(values, p),
     }
+}
 
-    let host_port = authority.rsplit('@').next().unwrap_or_default();
-    if host_port.is_empty() {
-        return "<invalid-host>".to_string();
+pub fn host_key(url: &str) -> String {
+    match ParsedUrl::parse(url) {
+        Ok(parsed) => parsed.host.unwrap_or_else(|| "<no-host>".to_string()),
+        Err(_) => "<invalid-host>".to_string(),
     }
+}
 
-    let host = if let Some(stripped) = host_port.strip_prefix('[') {
-        let Some((ipv6, _)) = stripped.split_once(']') else {
-            return "<invalid-host>".to_string();
-        };
-        if ipv6.is_empty() {
-            return "<invalid-host>".to_string();
+fn domain_key(url: &str) -> String {
+    let host = host_key(url);
+    domain::registrable_domain(&host).unwrap_or(host)
+}
+
+fn scheme_key(url: &str) -> String {
+    match ParsedUrl::parse(url) {
+        Ok(parsed) => parsed.scheme,
+        Err(_) => "<invalid-url>".to_string(),
+    }
+}
+
+fn path_key(url: &str) -> String {
+    match ParsedUrl::parse(url) {
+        Ok(parsed) => parsed.path,
+        Err(_) => "<invalid-url>".to_string(),
+    }
+}
+
+fn endpoint_key(entry: &HarEntry, rules: &EndpointRules) -> String {
+    match ParsedUrl::parse(&entry.request.url) {
+        Ok(parsed) => rules.endpoint(&entry.request.method, &parsed.raw_path),
+        Err(_) => "<invalid-url>".to_string(),
+    }
+}
+
+fn method_key(entry: &HarEntry) -> String {
+    let method = entry.request.method.trim();
+    if method.is_empty() {
+        "<no-method>".to_string()
+    } else {
+        method.to_ascii_uppercase()
+    }
+}
+
+fn status_key(entry: &HarEntry) -> String {
+    entry.response.status.to_string()
+}
+
+fn status_class_key(entry: &HarEntry) -> String {
+    match entry.response.status {
+        s @ 100..=599 => format!("{}xx", s / 100),
+        _ => "<no-status>".to_string(),
+    }
+}
+
+pub fn mime_type(entry: &HarEntry) -> String {
+    entry
+        .response
+        .content
+        .as_ref()
+        .map(|c| c.mime_type.split(';').next().unwrap_or_default())
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn mime_type_key(entry: &HarEntry) -> String {
+    let mime = mime_type(entry);
+    if mime.is_empty() {
+        "<no-mime-type>".to_string()
+    } else {
+        mime
+    }
+}
+
+fn resource_type_key(entry: &HarEntry) -> String {
+    if let Some(exported) = entry.custom.get("_resourceType").and_then(|v| v.as_str()) {
+        if !exported.is_empty() {
+            return exported.to_ascii_lowercase();
         }
-        format!("[{}]", ipv6)
+    }
+
+    let mime = mime_type(entry);
+    let resource_type = if mime == "text/html" || mime == "application/xhtml+xml" {
+        "document"
+    } else if mime == "text/css" {
+        "stylesheet"
+    } else if mime.contains("javascript") || mime.contains("ecmascript") {
+        "script"
+    } else if mime.starts_with("image/") {
+        "image"
+    } else if mime.starts_with("font/") || mime.contains("font") {
+        "font"
+    } else if mime.starts_with("audio/") || mime.starts_with("video/") {
+        "media"
+    } else if mime.contains("json") || mime.contains("xml") {
+        "fetch"
     } else {
-        host_port.split(':').next().unwrap_or_default().to_string()
+        "other"
     };
+    resource_type.to_string()
+}
 
-    if host.is_empty() {
-        "<invalid-host>".to_string()
+fn http_version_key(entry: &HarEntry) -> String {
+    let raw = if entry.response.http_version.trim().is_empty() {
+        entry.request.http_version.trim()
     } else {
-        host.to_ascii_lowercase()
+        entry.response.http_version.trim()
+    };
+
+    match raw.to_ascii_lowercase().as_str() {
+        "" | "unknown" => "<unknown>".to_string(),
+        "h2" | "http/2" | "http/2.0" => "HTTP/2".to_string(),
+        "h3" | "http/3" | "http/3.0" => "HTTP/3".to_string(),
+        other => other.to_ascii_uppercase(),
     }
 }
 
-fn build_top_groups(entries: &[HarEntry], top: usize, group_by: Option<GroupBy>) -> Vec<GroupRow> {
-    let Some(GroupBy::Host) = group_by else {
-        return Vec::new();
-    };
+fn page_key(entry: &HarEntry) -> String {
+    match entry.pageref.as_deref() {
+        Some(page) if !page.is_empty() => page.to_string(),
+        _ => "<no-page>".to_string(),
+    }
+}
 
-    let mut groups: HashMap<String, GroupAccumulator> = HashMap::new();
-    for entry in entries {
-        let key = host_key(&entry.request.url);
-        let acc = groups.entry(key).or_default();
-        acc.count += 1;
-        acc.total_time_ms += entry.time;
-        acc.total_bytes += entry_bytes(entry);
-        acc.times.push(entry.time);
-    }
-
-    let mut rows: Vec<GroupRow> = groups
-        .into_iter()
-        .map(|(key, mut acc)| {
-            acc.times
-                .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-            let p95_time_ms = nearest_rank_percentile(&acc.times, 0.95);
-            GroupRow {
-                key,
-                count: acc.count,
-                total_time_ms: acc.total_time_ms,
-                avg_time_ms: acc.total_time_ms / acc.count as f64,
-                p95_time_ms,
-                total_bytes: acc.total_bytes,
-            }
-        })
-        .collect();
-
-    rows.sort_by(|a, b| {
-        b.total_time_ms
-            .partial_cmp(&a.total_time_ms)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then_with(|| a.key.cmp(&b.key))
-    });
-    rows.into_iter().take(top).collect()
-}
-
-pub fn build_report(entries: &[HarEntry], top: usize, group_by: Option<GroupBy>) -> Report {
-    let total = entries.len();
-    let total_time_ms: f64 = entries.iter().map(|e| e.time).sum();
-    let total_bytes: u64 = entries.iter().map(entry_bytes).sum();
-
-    let mut by_time = entries.to_vec();
-    by_time.sort_by(|a, b| {
-        b.time
-            .partial_cmp(&a.time)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    let mut by_bytes = entries.to_vec();
-    by_bytes.sort_by_key(|e| std::cmp::Reverse(entry_bytes(e)));
-
-    let top_returned = top.min(total);
-    let top_groups = build_top_groups(entries, top, group_by);
-    let top_slowest = by_time
-        .into_iter()
-        .take(top_returned)
-        .map(|e| {
-            let bytes = entry_bytes(&e);
-            ReportRow {
-                url: e.request.url,
-                time_ms: e.time,
-                bytes,
+fn server_ip_key(entry: &HarEntry) -> String {
+    match entry.server_ip_address.as_deref().map(str::trim) {
+        Some(ip) if !ip.is_empty() => ip.trim_matches(['[', ']']).to_string(),
+        _ => "<unknown>".to_string(),
+    }
+}
+
+#[derive(Debug, Default)]
+struct ThirdPartyAccumulator {
+    requests: usize,
+    time_ms: f64,
+    bytes: u64,
+}
+
+/// Builds a [`Report`] incrementally so entries can be streamed in with bounded memory.
+#[derive(Debug)]
+pub struct ReportBuilder {
+    opts: ReportOptions,
+    sources: Vec<String>,
+    /// First parseable `startedDateTime` among the current file's entries, in `entries` order,
+    /// for the time window filter and the waterfall.
+    origin: Option<DateTime<FixedOffset>>,
+    seen: usize,
+    excluded_entries: usize,
+    entries: usize,
+    total_time_ms: f64,
+    total_bytes: u64,
+    times: QuantileSketch,
+    status_classes: BTreeMap<String, usize>,
+    slowest: TopK<ReportRow>,
+    largest: TopK<ReportRow>,
+    sorted: TopK<ReportRow>,
+    groups: HashMap<Vec<String>, GroupAccumulator>,
+    phases: PhaseAccumulator,
+    third_party: ThirdPartyAccumulator,
+    url_errors: Vec<UrlDiagnostic>,
+    url_error_index: HashMap<String, usize>,
+    skipped_entries: Vec<SkippedEntry>,
+    waterfall: Vec<WaterfallRow>,
+}
+
+impl ReportBuilder {
+    pub fn new(opts: ReportOptions) -> Self {
+        Self {
+            sources: Vec::new(),
+            origin: None,
+            seen: 0,
+            excluded_entries: 0,
+            entries: 0,
+            total_time_ms: 0.0,
+            total_bytes: 0,
+            times: QuantileSketch::default(),
+            status_classes: BTreeMap::new(),
+            slowest: TopK::new(opts.top),
+            largest: TopK::new(opts.top),
+            sorted: TopK::new(opts.top),
+            groups: HashMap::new(),
+            phases: PhaseAccumulator::default(),
+            third_party: ThirdPartyAccumulator::default(),
+            url_errors: Vec::new(),
+            url_error_index: HashMap::new(),
+            skipped_entries: Vec::new(),
+            waterfall: Vec::new(),
+            opts,
+        }
+    }
+
+    /// Attributes the entries pushed from now on to the input file `name`.
+    pub fn set_source(&mut self, name: &str) {
+        if self.sources.last().map(String::as_str) != Some(name) {
+            self.sources.push(name.to_string());
+        }
+        self.origin = None;
+    }
+
+    pub fn push(&mut self, entry: &HarEntry) {
+        let seq = self.seen;
+        self.seen += 1;
+        if (self.opts.filters.has_window() || self.opts.waterfall > 0) && self.origin.is_none() {
+            self.origin = filter::started_at(entry);
+        }
+        if !self.opts.filters.matches(entry, self.origin) {
+            self.excluded_entries += 1;
+            return;
+        }
+
+        let bytes = entry_bytes(entry);
+        let opts = &self.opts;
+        self.entries += 1;
+        self.total_time_ms += entry.time;
+        self.total_bytes += bytes;
+        self.times.push(entry.time);
+        *self
+            .status_classes
+            .entry(status_class_key(entry))
+            .or_default() += 1;
+
+        let source = self.sources.last();
+        let row = || report_row(entry, opts, source);
+        self.slowest.push_with(
+            Rank {
+                score: entry.time,
+                seq,
+            },
+            row,
+        );
+        self.largest.push_with(
+            Rank {
+                score: bytes as f64,
+                seq,
+            },
+            row,
+        );
+        let sort_score = match opts.sort_by {
+            Some(SortBy::Ttfb) => Some(entry_ttfb_ms(entry)),
+            // Lowest throughput ranks first; entries without receive time are skipped.
+            Some(SortBy::Throughput) => entry_throughput_bps(entry).map(|bps| -bps),
+            None => None,
+        };
+        if let Some(score) = sort_score {
+            self.sorted.push_with(Rank { score, seq }, row);
+        }
+
+        self.phases.push(&entry.timings);
+
+        let party = entry_party(entry, &opts.first_party);
+        if party == Some(Party::Third) {
+            self.third_party.requests += 1;
+            self.third_party.time_ms += entry.time;
+            self.third_party.bytes += bytes;
+        }
+
+        if let Some(group_by) = &opts.group_by {
+            let acc = self.groups.entry(group_by.keys(entry, opts)).or_default();
+            acc.total_bytes += bytes;
+            acc.times.push(entry.time);
+            acc.phases.push(&entry.timings);
+            acc.party = match (acc.party, party) {
+                (None, party) => party,
+                (Some(current), Some(party)) if current != party => Some(Party::Mixed),
+                (current, _) => current,
+            };
+        }
+
+        if self.waterfall.len() < opts.waterfall {
+            if let (Some(origin), Some(start)) = (self.origin, filter::started_at(entry)) {
+                let offset = start.signed_duration_since(origin);
+                self.waterfall.push(WaterfallRow {
+                    method: entry.request.method.clone(),
+                    url: entry.request.url.clone(),
+                    status: entry.response.status,
+                    start_ms: offset.num_microseconds().unwrap_or(0) as f64 / 1000.0,
+                    time_ms: entry.time,
+                    phases: TimingPhase::ALL
+                        .iter()
+                        .filter_map(|phase| {
+                            let ms = phase.value(&entry.timings)?;
+                            Some(PhaseSpan { phase: *phase, ms })
+                        })
+                        .collect(),
+                    source: source.cloned(),
+                });
             }
-        })
-        .collect();
-
-    let top_largest = by_bytes
-        .into_iter()
-        .take(top_returned)
-        .map(|e| {
-            let bytes = entry_bytes(&e);
-            ReportRow {
-                url: e.request.url,
-                time_ms: e.time,
-                bytes,
+        }
+
+        self.push_url_error(seq, &entry.request.url);
+    }
+
+    /// Records an entry from the current source that was skipped while parsing.
+    pub fn skip(&mut self, mut skipped: SkippedEntry) {
+        skipped.source = self.sources.last().cloned();
+        self.skipped_entries.push(skipped);
+    }
+
+    fn push_url_error(&mut self, seq: usize, url: &str) {
+        if let Some(&existing) = self.url_error_index.get(url) {
+            self.url_errors[existing].count += 1;
+            return;
+        }
+        if let Err(error) = ParsedUrl::parse(url) {
+            self.url_error_index
+                .insert(url.to_string(), self.url_errors.len());
+            self.url_errors.push(UrlDiagnostic {
+                url: url.to_string(),
+                error,
+                message: error.to_string(),
+                count: 1,
+                first_entry: seq,
+            });
+        }
+    }
+
+    pub fn finish(mut self) -> Report {
+        let opts = self.opts;
+        let mut top_groups: Vec<GroupRow> = match &opts.group_by {
+            Some(group_by) => self
+                .groups
+                .into_iter()
+                .map(|(keys, acc)| acc.finish(keys, group_by, &opts))
+                .collect(),
+            None => Vec::new(),
+        };
+        top_groups.sort_by(|a, b| {
+            b.total_time_ms
+                .partial_cmp(&a.total_time_ms)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        if !opts.all_groups {
+            top_groups.truncate(opts.top);
+        }
+        let time_percentiles = opts
+            .percentiles
+            .iter()
+            .map(|p| (*p, self.times.quantile(p / 100.0, opts.percentile_method)))
+            .collect();
+
+        let third_party = (!opts.first_party.is_empty()).then(|| {
+            let fraction = |part: f64, whole: f64| if whole > 0.0 { part / whole } else { 0.0 };
+            ThirdPartyShare {
+                requests: self.third_party.requests,
+                time_ms: self.third_party.time_ms,
+                bytes: self.third_party.bytes,
+                request_share: fraction(self.third_party.requests as f64, self.entries as f64),
+                time_share: fraction(self.third_party.time_ms, self.total_time_ms),
+                bytes_share: fraction(self.third_party.bytes as f64, self.total_bytes as f64),
             }
-        })
-        .collect();
+        });
+
+        Report {
+            sources: self.sources,
+            excluded_entries: self.excluded_entries,
+            entries: self.entries,
+            total_time_ms: self.total_time_ms,
+            total_bytes: self.total_bytes,
+            max_time_ms: self.times.max(),
+            time_percentiles: PercentileMap(time_percentiles),
+            status_classes: self.status_classes,
+            top_requested: opts.top,
+            top_returned: opts.top.min(self.entries),
+            sort_by: opts.sort_by,
+            percentile_method: opts.percentile_method,
+            top_slowest: self.slowest.into_sorted_vec(),
+            top_largest: self.largest.into_sorted_vec(),
+            top_sorted: self.sorted.into_sorted_vec(),
+            top_groups,
+            timing_phases: self.phases.finish(opts.percentile_method),
+            third_party,
+            url_errors: self.url_errors,
+            skipped_entries: self.skipped_entries,
+            waterfall: self.waterfall,
+            group_by: opts.group_by,
+            filters: opts.filters,
+        }
+    }
+}
 
-    Report {
-        entries: total,
-        total_time_ms,
-        total_bytes,
-        top_requested: top,
-        top_returned,
-        group_by,
-        top_slowest,
-        top_largest,
-        top_groups,
+/// Builds a report from entries already in memory; see [`ReportBuilder`] for streaming input.
+pub fn build_report(entries: &[HarEntry], opts: &ReportOptions) -> Report {
+    let mut builder = ReportBuilder::new(opts.clone());
+    for entry in entries {
+        builder.push(entry);
     }
+    builder.finish()
 }
 
 pub fn format_bytes(n: u64) -> String {
@@ -226,10 +1077,40 @@ pub fn format_bytes(n: u64) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::har::{HarEntry, HarRequest, HarResponse, HarResponseContent};
+    use crate::har::{HarEntry, HarRequest, HarResponse, HarResponseContent, HarTimings};
 
     use super::*;
 
+    fn opts(top: usize, group_by: Option<GroupBy>) -> ReportOptions {
+        ReportOptions {
+            top,
+            group_by: group_by.map(GroupSpec::from),
+            ..Default::default()
+        }
+    }
+
+    fn mk_timed_entry(url: &str, timings: [f64; 7]) -> HarEntry {
+        let [blocked, dns, connect, ssl, send, wait, receive] = timings;
+        HarEntry {
+            time: timings.iter().filter(|v| **v >= 0.0).sum(),
+            request: HarRequest {
+                url: url.to_string(),
+                ..Default::default()
+            },
+            timings: HarTimings {
+                blocked,
+                dns,
+                connect,
+                ssl,
+                send,
+                wait,
+                receive,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
     fn mk_entry(
         url: &str,
         time: f64,
@@ -241,12 +1122,18 @@ mod tests {
             time,
             request: HarRequest {
                 url: url.to_string(),
+                ..Default::default()
             },
             response: HarResponse {
                 body_size,
                 headers_size,
-                content: content_size.map(|size| HarResponseContent { size: Some(size) }),
+                content: content_size.map(|size| HarResponseContent {
+                    size: Some(size),
+                    ..Default::default()
+                }),
+                ..Default::default()
             },
+            ..Default::default()
         }
     }
 
@@ -275,7 +1162,7 @@ mod tests {
             mk_entry("https://c", 100.0, Some(-1), Some(7), Some(300)),
         ];
 
-        let report = build_report(&entries, 2, None);
+        let report = build_report(&entries, &opts(2, None));
         assert_eq!(report.entries, 3);
         assert_eq!(report.total_time_ms, 350.0);
         assert_eq!(report.total_bytes, 922);
@@ -292,12 +1179,48 @@ mod tests {
     #[test]
     fn build_report_caps_top_to_entry_count() {
         let entries = vec![mk_entry("https://one", 1.0, Some(1), Some(1), None)];
-        let report = build_report(&entries, 10, None);
+        let report = build_report(&entries, &opts(10, None));
         assert_eq!(report.top_returned, 1);
         assert_eq!(report.top_slowest.len(), 1);
         assert_eq!(report.top_largest.len(), 1);
     }
 
+    #[test]
+    fn build_report_summarizes_every_entry() {
+        let mut entries: Vec<HarEntry> = (1..=4)
+            .map(|i| {
+                let url = format!("https://host{}.example.com/", i);
+                mk_entry(&url, i as f64 * 100.0, Some(1), None, None)
+            })
+            .collect();
+        entries[0].response.status = 200;
+        entries[1].response.status = 503;
+        entries[2].response.status = 204;
+
+        let mut options = opts(1, Some(GroupBy::Host));
+        options.percentiles = vec![50.0, 95.0];
+        let report = build_report(&entries, &options);
+        assert_eq!(report.max_time_ms, 400.0);
+        assert_eq!(
+            report.time_percentiles.0,
+            vec![(50.0, 200.0), (95.0, 400.0)]
+        );
+        assert_eq!(
+            report.status_classes.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("2xx".to_string(), 2),
+                ("5xx".to_string(), 1),
+                ("<no-status>".to_string(), 1)
+            ]
+        );
+        assert_eq!(report.top_groups.len(), 1);
+
+        options.all_groups = true;
+        let report = build_report(&entries, &options);
+        assert_eq!(report.top_groups.len(), 4);
+        assert_eq!(report.top_slowest.len(), 1);
+    }
+
     #[test]
     fn nearest_rank_percentile_handles_small_samples() {
         assert_eq!(nearest_rank_percentile(&[], 0.95), 0.0);
@@ -334,7 +1257,7 @@ mod tests {
             mk_entry("https://cdn.example.com/d", 50.0, Some(50), Some(10), None),
         ];
 
-        let report = build_report(&entries, 2, Some(GroupBy::Host));
+        let report = build_report(&entries, &opts(2, Some(GroupBy::Host)));
         assert_eq!(report.top_groups.len(), 2);
 
         assert_eq!(report.top_groups[0].key, "cdn.example.com");
@@ -359,7 +1282,7 @@ mod tests {
             mk_entry("https://a.example.com/1", 100.0, Some(1), Some(0), None),
         ];
 
-        let report = build_report(&entries, 2, Some(GroupBy::Host));
+        let report = build_report(&entries, &opts(2, Some(GroupBy::Host)));
         assert_eq!(report.top_groups.len(), 2);
         assert_eq!(report.top_groups[0].key, "a.example.com");
         assert_eq!(report.top_groups[1].key, "z.example.com");
@@ -372,9 +1295,407 @@ mod tests {
             mk_entry("also bad", 20.0, Some(20), Some(0), None),
         ];
 
-        let report = build_report(&entries, 5, Some(GroupBy::Host));
+        let report = build_report(&entries, &opts(5, Some(GroupBy::Host)));
         assert_eq!(report.top_groups.len(), 1);
         assert_eq!(report.top_groups[0].key, "<invalid-host>");
         assert_eq!(report.top_groups[0].count, 2);
     }
+
+    #[test]
+    fn build_report_lists_url_parse_failures() {
+        let entries = vec![
+            mk_entry("not a url", 10.0, None, None, None),
+            mk_entry("https://ok.example.com/", 10.0, None, None, None),
+            mk_entry("https://bad.example.com:99999/", 10.0, None, None, None),
+            mk_entry("not a url", 10.0, None, None, None),
+        ];
+
+        let report = build_report(&entries, &opts(5, None));
+        assert_eq!(report.url_errors.len(), 2);
+        assert_eq!(report.url_errors[0].url, "not a url");
+        assert_eq!(report.url_errors[0].error, UrlError::Relative);
+        assert_eq!(report.url_errors[0].count, 2);
+        assert_eq!(report.url_errors[0].first_entry, 0);
+        assert_eq!(report.url_errors[1].error, UrlError::InvalidPort);
+        assert_eq!(report.url_errors[1].first_entry, 2);
+    }
+
+    #[test]
+    fn timing_phases_skip_not_applicable_values() {
+        let entries = vec![
+            mk_timed_entry(
+                "https://a.example.com/1",
+                [1.0, 10.0, 20.0, 15.0, 1.0, 100.0, 5.0],
+            ),
+            mk_timed_entry(
+                "https://a.example.com/2",
+                [3.0, -1.0, -1.0, -1.0, 1.0, 300.0, 15.0],
+            ),
+        ];
+
+        let report = build_report(&entries, &opts(5, None));
+        let phase = |p: TimingPhase| {
+            report
+                .timing_phases
+                .iter()
+                .find(|s| s.phase == p)
+                .expect("phase present")
+        };
+
+        assert_eq!(report.timing_phases.len(), TimingPhase::ALL.len());
+        assert_eq!(phase(TimingPhase::Dns).count, 1);
+        assert_eq!(phase(TimingPhase::Dns).total_ms, 10.0);
+        assert_eq!(phase(TimingPhase::Dns).avg_ms, 10.0);
+        assert_eq!(phase(TimingPhase::Wait).count, 2);
+        assert_eq!(phase(TimingPhase::Wait).total_ms, 400.0);
+        assert_eq!(phase(TimingPhase::Wait).avg_ms, 200.0);
+        assert_eq!(phase(TimingPhase::Wait).p50_ms, 100.0);
+        assert_eq!(phase(TimingPhase::Wait).p95_ms, 300.0);
+    }
+
+    #[test]
+    fn timing_phases_are_aggregated_per_group() {
+        let entries = vec![
+            mk_timed_entry(
+                "https://a.example.com/1",
+                [1.0, 10.0, 20.0, 15.0, 1.0, 100.0, 5.0],
+            ),
+            mk_timed_entry(
+                "https://b.example.com/1",
+                [1.0, -1.0, -1.0, -1.0, 1.0, 50.0, 5.0],
+            ),
+        ];
+
+        let report = build_report(&entries, &opts(5, Some(GroupBy::Host)));
+        let group_b = report
+            .top_groups
+            .iter()
+            .find(|g| g.key == "b.example.com")
+            .expect("group present");
+        let connect = group_b
+            .timing_phases
+            .iter()
+            .find(|s| s.phase == TimingPhase::Connect)
+            .expect("phase present");
+
+        assert_eq!(connect.count, 0);
+        assert_eq!(connect.total_ms, 0.0);
+        assert_eq!(connect.avg_ms, 0.0);
+    }
+
+    #[test]
+    fn ttfb_and_throughput_are_derived_from_timings() {
+        let mut e = mk_timed_entry("https://a", [1.0, 2.0, 3.0, 2.5, 4.0, 50.0, 250.0]);
+        e.response.body_size = Some(1000);
+        e.response.headers_size = Some(0);
+
+        assert_eq!(entry_ttfb_ms(&e), 60.0);
+        assert_eq!(entry_download_ms(&e), 250.0);
+        assert_eq!(entry_throughput_bps(&e), Some(4000.0));
+
+        let cached = mk_timed_entry("https://b", [-1.0, -1.0, -1.0, -1.0, 0.0, 5.0, 0.0]);
+        assert_eq!(entry_ttfb_ms(&cached), 5.0);
+        assert_eq!(entry_throughput_bps(&cached), None);
+    }
+
+    #[test]
+    fn build_report_sorts_by_ttfb_and_throughput() {
+        let mut entries = vec![
+            mk_timed_entry("https://fast-ttfb", [0.0, 0.0, 0.0, 0.0, 1.0, 10.0, 900.0]),
+            mk_timed_entry("https://slow-ttfb", [0.0, 0.0, 0.0, 0.0, 1.0, 400.0, 10.0]),
+            mk_timed_entry("https://no-receive", [0.0, 0.0, 0.0, 0.0, 1.0, 20.0, 0.0]),
+        ];
+        for e in &mut entries {
+            e.response.body_size = Some(9000);
+        }
+
+        let mut options = opts(2, None);
+        options.sort_by = Some(SortBy::Ttfb);
+        let report = build_report(&entries, &options);
+        assert_eq!(report.top_sorted.len(), 2);
+        assert_eq!(report.top_sorted[0].url, "https://slow-ttfb");
+        assert_eq!(report.top_sorted[1].url, "https://no-receive");
+
+        options.sort_by = Some(SortBy::Throughput);
+        let report = build_report(&entries, &options);
+        assert_eq!(report.top_sorted.len(), 2);
+        assert_eq!(report.top_sorted[0].url, "https://fast-ttfb");
+        assert_eq!(report.top_sorted[0].throughput_bps, Some(10000.0));
+        assert_eq!(report.top_sorted[1].url, "https://slow-ttfb");
+    }
+
+    #[test]
+    fn linear_percentile_interpolates_between_ranks() {
+        let values = [10.0, 20.0, 30.0, 40.0];
+        assert_eq!(linear_percentile(&[], 0.5), 0.0);
+        assert_eq!(linear_percentile(&[7.0], 0.99), 7.0);
+        assert_eq!(linear_percentile(&values, 0.0), 10.0);
+        assert_eq!(linear_percentile(&values, 0.5), 25.0);
+        assert_eq!(linear_percentile(&values, 1.0), 40.0);
+        assert!((linear_percentile(&values, 0.9) - 37.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn group_rows_include_configured_percentiles_and_spread() {
+        let entries = vec![
+            mk_entry("https://a.example.com/1", 10.0, None, None, None),
+            mk_entry("https://a.example.com/2", 20.0, None, None, None),
+            mk_entry("https://a.example.com/3", 30.0, None, None, None),
+            mk_entry("https://a.example.com/4", 40.0, None, None, None),
+        ];
+
+        let mut options = opts(5, Some(GroupBy::Host));
+        options.percentiles = vec![50.0, 99.9];
+        let report = build_report(&entries, &options);
+        let group = &report.top_groups[0];
+        assert_eq!(group.min_time_ms, 10.0);
+        assert_eq!(group.max_time_ms, 40.0);
+        assert_eq!(group.median_time_ms, 20.0);
+        assert!((group.stddev_time_ms - 125f64.sqrt()).abs() < 1e-9);
+        assert_eq!(group.percentiles.0, vec![(50.0, 20.0), (99.9, 40.0)]);
+
+        options.percentile_method = PercentileMethod::Linear;
+        let report = build_report(&entries, &options);
+        let group = &report.top_groups[0];
+        assert_eq!(group.median_time_ms, 25.0);
+        assert_eq!(group.percentiles.0[0], (50.0, 25.0));
+    }
+
+    #[test]
+    fn percentile_map_serializes_in_requested_order() {
+        let map = PercentileMap(vec![(99.0, 3.0), (50.0, 1.0), (99.9, 4.0)]);
+        let json = serde_json::to_string(&map).expect("serializes");
+        assert_eq!(json, r#"{"p99":3.0,"p50":1.0,"p99.9":4.0}"#);
+    }
+
+    #[test]
+    fn host_key_ignores_query_and_fragment_without_path() {
+        assert_eq!(host_key("https://A.example.com?x=1"), "a.example.com");
+        assert_eq!(host_key("https://a.example.com#top"), "a.example.com");
+        assert_eq!(host_key("https://user@[::1]:8443/x"), "[::1]");
+        assert_eq!(host_key("https://bücher.example/"), "xn--bcher-kva.example");
+        assert_eq!(host_key("data:text/plain,hi"), "<no-host>");
+    }
+
+    #[test]
+    fn url_dimension_keys() {
+        assert_eq!(scheme_key("HTTPS://example.com/"), "https");
+        assert_eq!(scheme_key("not a url"), "<invalid-url>");
+        assert_eq!(path_key("https://example.com/a/b?q=1#f"), "/a/b");
+        assert_eq!(path_key("https://example.com?q=1"), "/");
+        assert_eq!(path_key("nope"), "<invalid-url>");
+        assert_eq!(path_key("https://example.com/caf%C3%A9"), "/café");
+    }
+
+    #[test]
+    fn entry_dimension_keys() {
+        let mut e = mk_entry("https://example.com/", 1.0, None, None, None);
+        e.request.method = "post".to_string();
+        e.response.status = 503;
+        e.response.http_version = "h2".to_string();
+        e.response.content = Some(HarResponseContent {
+            mime_type: "Application/JSON; charset=utf-8".to_string(),
+            ..Default::default()
+        });
+        e.server_ip_address = Some("[2001:db8::1]".to_string());
+
+        let o = ReportOptions::default();
+        assert_eq!(GroupBy::Method.key(&e, &o), "POST");
+        assert_eq!(GroupBy::Status.key(&e, &o), "503");
+        assert_eq!(GroupBy::StatusClass.key(&e, &o), "5xx");
+        assert_eq!(GroupBy::MimeType.key(&e, &o), "application/json");
+        assert_eq!(GroupBy::ResourceType.key(&e, &o), "fetch");
+        assert_eq!(GroupBy::HttpVersion.key(&e, &o), "HTTP/2");
+        assert_eq!(GroupBy::Page.key(&e, &o), "<no-page>");
+        assert_eq!(GroupBy::ServerIp.key(&e, &o), "2001:db8::1");
+
+        e.custom.insert("_resourceType".to_string(), "XHR".into());
+        e.response.status = 0;
+        e.pageref = Some("page_1".to_string());
+        assert_eq!(GroupBy::ResourceType.key(&e, &o), "xhr");
+        assert_eq!(GroupBy::StatusClass.key(&e, &o), "<no-status>");
+        assert_eq!(GroupBy::Page.key(&e, &o), "page_1");
+    }
+
+    #[test]
+    fn build_report_groups_by_multiple_dimensions() {
+        let mut entries = vec![
+            mk_entry("https://api.example.com/a", 100.0, None, None, None),
+            mk_entry("https://api.example.com/b", 300.0, None, None, None),
+            mk_entry("https://api.example.com/c", 50.0, None, None, None),
+            mk_entry("https://cdn.example.com/d", 10.0, None, None, None),
+        ];
+        for (entry, status) in entries.iter_mut().zip([200, 503, 500, 200]) {
+            entry.response.status = status;
+        }
+
+        let options = ReportOptions {
+            top: 10,
+            group_by: Some(GroupSpec(vec![GroupBy::Host, GroupBy::StatusClass])),
+            ..Default::default()
+        };
+        let report = build_report(&entries, &options);
+
+        assert_eq!(report.top_groups.len(), 3);
+        let top = &report.top_groups[0];
+        assert_eq!(top.key, "api.example.com | 5xx");
+        assert_eq!(top.count, 2);
+        assert_eq!(top.total_time_ms, 350.0);
+        assert_eq!(
+            top.dimensions.0,
+            vec![
+                (GroupBy::Host, "api.example.com".to_string()),
+                (GroupBy::StatusClass, "5xx".to_string()),
+            ]
+        );
+        assert_eq!(report.top_groups[1].key, "api.example.com | 2xx");
+        assert_eq!(report.top_groups[2].key, "cdn.example.com | 2xx");
+    }
+
+    #[test]
+    fn group_spec_parses_distinct_dimensions_only() {
+        assert_eq!(
+            parse_group_spec("host, status-class"),
+            Ok(GroupSpec(vec![GroupBy::Host, GroupBy::StatusClass]))
+        );
+        let err = parse_group_spec("host,method,host").expect_err("repeated dimension");
+        assert!(err.contains("given twice: host"), "{}", err);
+        assert!(parse_group_spec("hosts").is_err());
+    }
+
+    #[test]
+    fn group_by_domain_collapses_subdomains() {
+        let entries = vec![
+            mk_entry("https://a.cdn.example.com/1", 10.0, None, None, None),
+            mk_entry("https://b.cdn.example.com/2", 20.0, None, None, None),
+            mk_entry("https://www.shop.co.uk/", 5.0, None, None, None),
+            mk_entry("http://127.0.0.1:8080/", 1.0, None, None, None),
+        ];
+
+        let report = build_report(&entries, &opts(10, Some(GroupBy::Domain)));
+        let keys: Vec<&str> = report.top_groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["example.com", "shop.co.uk", "127.0.0.1"]);
+        assert_eq!(report.top_groups[0].count, 2);
+    }
+
+    #[test]
+    fn first_party_option_tags_rows_groups_and_third_party_share() {
+        let entries = vec![
+            mk_entry("https://www.example.com/", 300.0, Some(700), Some(0), None),
+            mk_entry("https://api.example.com/x", 100.0, Some(100), Some(0), None),
+            mk_entry(
+                "https://tracker.ads.test/p",
+                100.0,
+                Some(200),
+                Some(0),
+                None,
+            ),
+        ];
+
+        let mut options = opts(10, Some(GroupBy::Domain));
+        options.first_party = vec!["example.com".to_string()];
+        let report = build_report(&entries, &options);
+
+        assert_eq!(report.top_slowest[0].party, Some(Party::First));
+        assert_eq!(report.top_slowest[2].party, Some(Party::Third));
+        assert_eq!(report.top_groups[0].key, "example.com");
+        assert_eq!(report.top_groups[0].party, Some(Party::First));
+        assert_eq!(report.top_groups[1].party, Some(Party::Third));
+
+        let share = report.third_party.expect("third-party share");
+        assert_eq!(share.requests, 1);
+        assert_eq!(share.time_ms, 100.0);
+        assert_eq!(share.bytes, 200);
+        assert_eq!(share.time_share, 0.2);
+        assert_eq!(share.bytes_share, 0.2);
+
+        let report = build_report(&entries, &opts(10, Some(GroupBy::Method)));
+        assert!(report.third_party.is_none());
+        assert_eq!(report.top_groups[0].party, None);
+
+        options.group_by = Some(GroupBy::Method.into());
+        let report = build_report(&entries, &options);
+        assert_eq!(report.top_groups[0].party, Some(Party::Mixed));
+    }
+
+    #[test]
+    fn group_by_endpoint_templates_rest_paths() {
+        let mut entries = vec![
+            mk_entry(
+                "https://api.example.com/users/1/orders",
+                10.0,
+                None,
+                None,
+                None,
+            ),
+            mk_entry(
+                "https://api.example.com/users/22/orders?page=2",
+                30.0,
+                None,
+                None,
+                None,
+            ),
+            mk_entry("https://api.example.com/users/22", 5.0, None, None, None),
+        ];
+        entries[2].request.method = "DELETE".to_string();
+
+        let report = build_report(&entries, &opts(10, Some(GroupBy::Endpoint)));
+        let groups = &report.top_groups;
+        assert_eq!(groups[0].key, "/users/{id}/orders");
+        assert_eq!(groups[0].count, 2);
+        assert_eq!(groups[1].key, "/users/{id}");
+
+        let mut options = opts(10, Some(GroupBy::Endpoint));
+        options.endpoints.with_method = true;
+        let report = build_report(&entries, &options);
+        assert_eq!(report.top_groups[1].key, "DELETE /users/{id}");
+    }
+
+    #[test]
+    fn builder_keeps_top_n_with_ties_in_input_order() {
+        let entries: Vec<HarEntry> = (0..1000)
+            .map(|i| {
+                let url = format!("https://example.com/{i}");
+                mk_entry(&url, (i % 100) as f64, Some(i % 7), None, None)
+            })
+            .collect();
+
+        let report = build_report(&entries, &opts(3, None));
+        assert_eq!(report.entries, 1000);
+        assert_eq!(report.top_returned, 3);
+        let slowest: Vec<&str> = report.top_slowest.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(
+            slowest,
+            [
+                "https://example.com/99",
+                "https://example.com/199",
+                "https://example.com/299"
+            ]
+        );
+        let largest: Vec<&str> = report.top_largest.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(
+            largest,
+            [
+                "https://example.com/6",
+                "https://example.com/13",
+                "https://example.com/20"
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_summarizes_large_groups_approximately() {
+        let entries: Vec<HarEntry> = (1..=20_000)
+            .map(|i| mk_entry("https://example.com/", i as f64, None, None, None))
+            .collect();
+
+        let report = build_report(&entries, &opts(10, Some(GroupBy::Host)));
+        let group = &report.top_groups[0];
+        assert_eq!(group.count, 20_000);
+        assert_eq!(group.total_time_ms, 200_010_000.0);
+        assert_eq!(group.min_time_ms, 1.0);
+        assert_eq!(group.max_time_ms, 20_000.0);
+        assert!((group.median_time_ms - 10_000.0).abs() <= 100.0);
+        assert!((group.p95_time_ms - 19_000.0).abs() <= 190.0);
+    }
 }
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use crate::report::{percentile, PercentileMethod};
+
+/// Samples kept verbatim before switching to the bucketed approximation.
+const EXACT_LIMIT: usize = 4096;
+/// Maximum relative error of quantiles once the sketch has been compressed.
+const RELATIVE_ACCURACY: f64 = 0.01;
+/// Values at or below this are counted in the zero bucket.
+const MIN_POSITIVE: f64 = 1e-9;
+
+fn gamma() -> f64 {
+    (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY)
+}
+
+/// Streaming summary of a series of non-negative samples with bounded memory.
+///
+/// Count, sum, mean, min, max and standard deviation are exact. Quantiles are exact for
+/// up to [`EXACT_LIMIT`] samples and within [`RELATIVE_ACCURACY`] afterwards, using
+/// logarithmically sized buckets (as in DDSketch).
+#[derive(Debug, Clone)]
+pub struct QuantileSketch {
+    count: usize,
+    sum: f64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    exact: Option<Vec<f64>>,
+    sorted: bool,
+    zero_count: usize,
+    buckets: BTreeMap<i32, usize>,
+}
+
+impl Default for QuantileSketch {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            exact: Some(Vec::new()),
+            sorted: true,
+            zero_count: 0,
+            buckets: BTreeMap::new(),
+        }
+    }
+}
+
+impl QuantileSketch {
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        match &mut self.exact {
+            Some(values) if values.len() < EXACT_LIMIT => {
+                values.push(value);
+                self.sorted = false;
+            }
+            Some(_) => {
+                self.compress();
+                self.add_to_bucket(value);
+            }
+            None => self.add_to_bucket(value),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn min(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.min
+        }
+    }
+
+    pub fn max(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.max
+        }
+    }
+
+    /// Population standard deviation.
+    pub fn stddev(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.m2 / self.count as f64).sqrt()
+        }
+    }
+
+    /// Whether quantiles are still computed from the raw samples.
+    #[cfg(test)]
+    pub fn is_exact(&self) -> bool {
+        self.exact.is_some()
+    }
+
+    /// Quantile for `p` in `[0, 1]`; `0.0` when empty.
+    pub fn quantile(&mut self, p: f64, method: PercentileMethod) -> f64 {
+        if let Some(values) = &mut self.exact {
+            if !self.sorted {
+                values.sort_by(f64::total_cmp);
+                self.sorted = true;
+            }
+            return percentile(values, p, method);
+        }
+
+        let n = self.count;
+        let estimate = match method {
+            PercentileMethod::NearestRank => {
+                let rank = (p * n as f64).ceil() as usize;
+                self.value_at_rank(rank.clamp(1, n))
+            }
+            PercentileMethod::Linear => {
+                let h = p.clamp(0.0, 1.0) * (n - 1) as f64;
+                let lo = self.value_at_rank(h.floor() as usize + 1);
+                let hi = self.value_at_rank(h.ceil() as usize + 1);
+                lo + (h - h.floor()) * (hi - lo)
+            }
+        };
+        estimate.clamp(self.min, self.max)
+    }
+
+    fn compress(&mut self) {
+        if let Some(values) = self.exact.take() {
+            for value in values {
+                self.add_to_bucket(value);
+            }
+        }
+    }
+
+    fn add_to_bucket(&mut self, value: f64) {
+        if value <= MIN_POSITIVE {
+            self.zero_count += 1;
+        } else {
+            let index = (value.ln() / gamma().ln()).ceil() as i32;
+            *self.buckets.entry(index).or_default() += 1;
+        }
+    }
+
+    /// Representative value of the sample with 1-based `rank` in sorted order.
+    fn value_at_rank(&self, rank: usize) -> f64 {
+        if rank <= self.zero_count {
+            return 0.0;
+        }
+
+        let mut seen = self.zero_count;
+        for (index, count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                return 2.0 * gamma().powi(*index) / (gamma() + 1.0);
+            }
+        }
+        self.max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_samples_are_exact() {
+        let mut sketch = QuantileSketch::default();
+        for v in [40.0, 10.0, 30.0, 20.0] {
+            sketch.push(v);
+        }
+
+        assert!(sketch.is_exact());
+        assert_eq!(sketch.count(), 4);
+        assert_eq!(sketch.sum(), 100.0);
+        assert_eq!(sketch.mean(), 25.0);
+        assert_eq!(sketch.min(), 10.0);
+        assert_eq!(sketch.max(), 40.0);
+        assert!((sketch.stddev() - 125f64.sqrt()).abs() < 1e-9);
+        assert_eq!(sketch.quantile(0.5, PercentileMethod::NearestRank), 20.0);
+        assert_eq!(sketch.quantile(0.5, PercentileMethod::Linear), 25.0);
+    }
+
+    #[test]
+    fn empty_sketch_reports_zero() {
+        let mut sketch = QuantileSketch::default();
+        assert_eq!(sketch.min(), 0.0);
+        assert_eq!(sketch.max(), 0.0);
+        assert_eq!(sketch.stddev(), 0.0);
+        assert_eq!(sketch.quantile(0.95, PercentileMethod::NearestRank), 0.0);
+    }
+
+    #[test]
+    fn large_samples_stay_within_relative_accuracy() {
+        let mut sketch = QuantileSketch::default();
+        let n = 100_000;
+        for i in 0..n {
+            sketch.push(i as f64);
+        }
+
+        assert!(!sketch.is_exact());
+        assert_eq!(sketch.count(), n);
+        assert_eq!(sketch.min(), 0.0);
+        assert_eq!(sketch.max(), (n - 1) as f64);
+        for (p, expected) in [(0.5, 49_999.0), (0.95, 94_999.0), (0.999, 99_899.0)] {
+            for method in [PercentileMethod::NearestRank, PercentileMethod::Linear] {
+                let estimate = sketch.quantile(p, method);
+                let error = (estimate - expected).abs() / expected;
+                assert!(error <= RELATIVE_ACCURACY, "p{p}: {estimate} vs {expected}");
+            }
+        }
+    }
+}
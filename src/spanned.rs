@@ -0,0 +1,621 @@
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// Maximum nesting of arrays and objects before parsing gives up.
+const MAX_DEPTH: usize = 256;
+
+/// 1-based line and column (in characters) of a token in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A JSON value annotated with where it starts in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    pub value: Json,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Spanned>),
+    /// Members in source order; duplicate keys are kept.
+    Object(Vec<(String, Spanned)>),
+}
+
+impl Json {
+    /// JSON type name used in diagnostics.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Bool(_) => "boolean",
+            Json::Number(_) => "number",
+            Json::String(_) => "string",
+            Json::Array(_) => "array",
+            Json::Object(_) => "object",
+        }
+    }
+}
+
+impl Spanned {
+    /// First member named `key`, if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Spanned> {
+        match &self.value {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub pos: Position,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.pos.line, self.pos.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a complete JSON document, keeping the position of every value.
+pub fn parse(text: &str) -> Result<Spanned, ParseError> {
+    let mut stream = Stream::new(text.chars());
+    let value = stream.value()?;
+    stream.finish()?;
+    Ok(value)
+}
+
+/// Pull parser for documents too large to hold as one value: containers are entered and
+/// walked member by member, and only the values asked for are built.
+pub struct Stream<I: Iterator<Item = char>> {
+    parser: Parser<I>,
+    /// One flag per entered container, set until its first member is read.
+    first: Vec<bool>,
+}
+
+impl<I: Iterator<Item = char>> Stream<I> {
+    pub fn new(chars: I) -> Self {
+        Self {
+            parser: Parser {
+                chars: chars.peekable(),
+                pos: Position { line: 1, column: 1 },
+                depth: 0,
+            },
+            first: Vec::new(),
+        }
+    }
+
+    /// Position of the next token.
+    pub fn position(&mut self) -> Position {
+        self.parser.skip_whitespace();
+        self.parser.pos
+    }
+
+    /// The next token's first character, without consuming it.
+    pub fn peek(&mut self) -> Option<char> {
+        self.parser.skip_whitespace();
+        self.parser.chars.peek().copied()
+    }
+
+    /// Enters the object or array starting at the next token.
+    pub fn enter(&mut self) -> Result<(), ParseError> {
+        self.parser.skip_whitespace();
+        if self.parser.depth == MAX_DEPTH {
+            return Err(self.parser.error("nesting too deep"));
+        }
+        match self.parser.chars.peek() {
+            Some('{' | '[') => {
+                self.parser.bump();
+                self.parser.depth += 1;
+                self.first.push(true);
+                Ok(())
+            }
+            _ => Err(self.parser.error("expected '{' or '['")),
+        }
+    }
+
+    /// Reads the next key of the entered object, or leaves it and returns `None` at its end.
+    pub fn next_key(&mut self) -> Result<Option<String>, ParseError> {
+        if !self.next_member('}', "expected ',' or '}' after object member")? {
+            return Ok(None);
+        }
+        self.parser.skip_whitespace();
+        if self.parser.chars.peek() != Some(&'"') {
+            return Err(self.parser.error("expected object key"));
+        }
+        let key = self.parser.string()?;
+        self.parser.skip_whitespace();
+        self.parser.expect(':')?;
+        Ok(Some(key))
+    }
+
+    /// Moves to the next element of the entered array, or leaves it and returns false at its
+    /// end.
+    pub fn next_element(&mut self) -> Result<bool, ParseError> {
+        self.next_member(']', "expected ',' or ']' after array element")
+    }
+
+    fn next_member(&mut self, close: char, message: &str) -> Result<bool, ParseError> {
+        self.parser.skip_whitespace();
+        let first = self.first.last_mut().expect("inside a container");
+        let more = if *first {
+            *first = false;
+            if self.parser.chars.peek() == Some(&close) {
+                self.parser.bump();
+                false
+            } else {
+                true
+            }
+        } else {
+            let pos = self.parser.pos;
+            match self.parser.bump() {
+                Some(',') => true,
+                Some(c) if c == close => false,
+                _ => {
+                    return Err(ParseError {
+                        message: message.to_string(),
+                        pos,
+                    })
+                }
+            }
+        };
+        if !more {
+            self.first.pop();
+            self.parser.depth -= 1;
+        }
+        Ok(more)
+    }
+
+    /// Parses the next value whole.
+    pub fn value(&mut self) -> Result<Spanned, ParseError> {
+        self.parser.skip_whitespace();
+        self.parser.value()
+    }
+
+    /// Skips the next value without building it and returns its type name.
+    pub fn skip(&mut self) -> Result<&'static str, ParseError> {
+        match self.peek() {
+            Some('{') => {
+                self.enter()?;
+                while self.next_key()?.is_some() {
+                    self.skip()?;
+                }
+                Ok("object")
+            }
+            Some('[') => {
+                self.enter()?;
+                while self.next_element()? {
+                    self.skip()?;
+                }
+                Ok("array")
+            }
+            _ => Ok(self.value()?.value.type_name()),
+        }
+    }
+
+    /// Checks that nothing but whitespace follows the document.
+    pub fn finish(&mut self) -> Result<(), ParseError> {
+        if self.peek().is_some() {
+            return Err(self.parser.error("trailing characters after JSON value"));
+        }
+        Ok(())
+    }
+}
+
+/// Characters decoded from UTF-8 input. Iteration stops at the first read or decoding error,
+/// which is kept for the caller to check once parsing stops.
+pub struct ReadChars<R> {
+    reader: R,
+    pub io_error: Option<io::Error>,
+    pub invalid_utf8: bool,
+}
+
+impl<R: BufRead> ReadChars<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            io_error: None,
+            invalid_utf8: false,
+        }
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok([]) => return None,
+                Ok(buf) => {
+                    let byte = buf[0];
+                    self.reader.consume(1);
+                    return Some(byte);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.io_error = Some(err);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ReadChars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.io_error.is_some() || self.invalid_utf8 {
+            return None;
+        }
+        let lead = self.next_byte()?;
+        let len = match lead {
+            0x00..=0x7f => return Some(lead as char),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+        let mut bytes = [lead, 0, 0, 0];
+        let mut complete = len > 0;
+        for byte in bytes.iter_mut().take(len).skip(1) {
+            match self.next_byte() {
+                Some(b) => *byte = b,
+                None => {
+                    complete = false;
+                    break;
+                }
+            }
+        }
+        let decoded = complete
+            .then(|| std::str::from_utf8(&bytes[..len]).ok())
+            .flatten()
+            .and_then(|s| s.chars().next());
+        if decoded.is_none() && self.io_error.is_none() {
+            self.invalid_utf8 = true;
+        }
+        decoded
+    }
+}
+
+struct Parser<I: Iterator<Item = char>> {
+    chars: std::iter::Peekable<I>,
+    pos: Position,
+    depth: usize,
+}
+
+impl<I: Iterator<Item = char>> Parser<I> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+            pos: self.pos,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.chars.peek() {
+            Some(&c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(&c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', found end of input", expected))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Spanned, ParseError> {
+        let pos = self.pos;
+        let value = match self.chars.peek().copied() {
+            Some('{') => self.nested(Self::object)?,
+            Some('[') => self.nested(Self::array)?,
+            Some('"') => Json::String(self.string()?),
+            Some('t') => self.literal("true", Json::Bool(true))?,
+            Some('f') => self.literal("false", Json::Bool(false))?,
+            Some('n') => self.literal("null", Json::Null)?,
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(c) => return Err(self.error(format!("expected value, found '{}'", c))),
+            None => return Err(self.error("expected value, found end of input")),
+        };
+        Ok(Spanned { value, pos })
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, ParseError>,
+    ) -> Result<Json, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, ParseError> {
+        let start = self.error(format!("expected '{}'", word));
+        for expected in word.chars() {
+            if self.bump() != Some(expected) {
+                return Err(start);
+            }
+        }
+        Ok(value)
+    }
+
+    fn object(&mut self) -> Result<Json, ParseError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.bump();
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(self.error("expected object key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            let pos = self.pos;
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => {
+                    return Err(ParseError {
+                        message: "expected ',' or '}' after object member".to_string(),
+                        pos,
+                    })
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.bump();
+            return Ok(Json::Array(items));
+        }
+        loop {
+            self.skip_whitespace();
+            items.push(self.value()?);
+            self.skip_whitespace();
+            let pos = self.pos;
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => {
+                    return Err(ParseError {
+                        message: "expected ',' or ']' after array element".to_string(),
+                        pos,
+                    })
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(out),
+                Some('\\') => {
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    out.push(c);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("control character in string"));
+                }
+                Some(c) => out.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .bump()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("invalid unicode escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.hex4()?;
+        if (0xd800..0xdc00).contains(&high) {
+            if self.bump() != Some('\\') || self.bump() != Some('u') {
+                return Err(self.error("unpaired surrogate in unicode escape"));
+            }
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("unpaired surrogate in unicode escape"));
+            }
+            let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+            return char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"));
+        }
+        char::from_u32(high).ok_or_else(|| self.error("unpaired surrogate in unicode escape"))
+    }
+
+    fn number(&mut self) -> Result<Json, ParseError> {
+        let start = self.pos;
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if !is_json_number(&text) {
+            return Err(ParseError {
+                message: format!("invalid number '{}'", text),
+                pos: start,
+            });
+        }
+        text.parse().map(Json::Number).map_err(|_| ParseError {
+            message: format!("invalid number '{}'", text),
+            pos: start,
+        })
+    }
+}
+
+/// Checks the JSON number grammar, which is stricter than `f64::from_str`.
+fn is_json_number(text: &str) -> bool {
+    let rest = text.strip_prefix('-').unwrap_or(text);
+    let int_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (int, mut rest) = rest.split_at(int_len);
+    if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+        return false;
+    }
+    if let Some(frac) = rest.strip_prefix('.') {
+        let len = frac
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(frac.len());
+        if len == 0 {
+            return false;
+        }
+        rest = &frac[len..];
+    }
+    if let Some(exp) = rest.strip_prefix(['e', 'E']) {
+        let exp = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+        return !exp.is_empty() && exp.chars().all(|c| c.is_ascii_digit());
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_positions_of_nested_values() {
+        let doc =
+            parse("{\n  \"a\": [1, {\"b\": null}],\n  \"c\": \"x\\u00e9\"\n}").expect("valid");
+        assert_eq!(doc.pos, Position { line: 1, column: 1 });
+        let a = doc.get("a").expect("a");
+        assert_eq!(a.pos, Position { line: 2, column: 8 });
+        let Json::Array(items) = &a.value else {
+            panic!("array expected");
+        };
+        assert_eq!(items[0].value, Json::Number(1.0));
+        assert_eq!(
+            items[1].pos,
+            Position {
+                line: 2,
+                column: 12
+            }
+        );
+        assert_eq!(items[1].get("b").map(|b| &b.value), Some(&Json::Null));
+        let c = doc.get("c").expect("c");
+        assert_eq!(c.value, Json::String("xé".to_string()));
+        assert_eq!(c.pos, Position { line: 3, column: 8 });
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_position() {
+        let err = parse("{\n  \"a\": 1,\n  \"b\" 2\n}").expect_err("missing colon");
+        assert_eq!(err.message, "expected ':', found '2'");
+        assert_eq!(err.pos, Position { line: 3, column: 7 });
+
+        let err = parse("[1, 2").expect_err("unterminated");
+        assert_eq!(err.pos, Position { line: 1, column: 6 });
+        assert!(parse("{} x").is_err());
+        assert!(parse("\"\\ud800\"").is_err());
+    }
+
+    #[test]
+    fn stream_walks_containers_member_by_member() {
+        let mut stream = Stream::new("{\"a\": [1, {\"b\": [true]}], \"c\": [\n 2 ,3]}".chars());
+        stream.enter().expect("object");
+        assert_eq!(stream.next_key().expect("key").as_deref(), Some("a"));
+        assert_eq!(stream.skip().expect("skip"), "array");
+        assert_eq!(stream.next_key().expect("key").as_deref(), Some("c"));
+        stream.enter().expect("array");
+        let mut items = Vec::new();
+        while stream.next_element().expect("element") {
+            items.push(stream.value().expect("value"));
+        }
+        assert_eq!(items[1].value, Json::Number(3.0));
+        assert_eq!(items[1].pos, Position { line: 2, column: 5 });
+        assert_eq!(stream.next_key().expect("end"), None);
+        stream.finish().expect("no trailing characters");
+
+        let mut stream = Stream::new("[1 2]".chars());
+        stream.enter().expect("array");
+        assert!(stream.next_element().expect("first"));
+        stream.value().expect("value");
+        let err = stream.next_element().expect_err("missing comma");
+        assert_eq!(err.message, "expected ',' or ']' after array element");
+        assert_eq!(err.pos, Position { line: 1, column: 4 });
+    }
+
+    #[test]
+    fn validates_number_grammar() {
+        for ok in ["0", "-1", "1.5", "1e10", "-0.5E-3"] {
+            assert!(is_json_number(ok), "{ok}");
+        }
+        for bad in ["01", "1.", ".5", "+1", "1e", "--1", "1-2"] {
+            assert!(!is_json_number(bad), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use serde::Serialize;
+
+use crate::report::{percentile, PercentileMethod};
+
+/// Largest `n1 * n2` for which Mann-Whitney p-values are computed exactly.
+const EXACT_MAX_PAIRS: usize = 400;
+
+/// Result of a two-sided Mann-Whitney U test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// Pairs `(a, b)` with `b > a`, ties counting half.
+    pub u: f64,
+    pub p_value: f64,
+}
+
+/// Tests whether `b` tends to be larger or smaller than `a` without assuming a distribution.
+///
+/// Small samples without ties get the exact p-value; otherwise the normal approximation with
+/// tie and continuity correction is used.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> MannWhitney {
+    let (n1, n2) = (a.len(), b.len());
+    if n1 == 0 || n2 == 0 {
+        return MannWhitney {
+            u: 0.0,
+            p_value: 1.0,
+        };
+    }
+
+    let mut pooled: Vec<(f64, bool)> = a
+        .iter()
+        .map(|v| (*v, false))
+        .chain(b.iter().map(|v| (*v, true)))
+        .collect();
+    pooled.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Average ranks over runs of ties, remembering tie sizes for the variance correction.
+    let mut rank_sum_b = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < pooled.len() {
+        let mut j = i + 1;
+        while j < pooled.len() && pooled[j].0 == pooled[i].0 {
+            j += 1;
+        }
+        let rank = (i + j + 1) as f64 / 2.0;
+        rank_sum_b += rank * pooled[i..j].iter().filter(|(_, in_b)| *in_b).count() as f64;
+        let t = (j - i) as f64;
+        tie_term += t * t * t - t;
+        i = j;
+    }
+
+    let pairs = (n1 * n2) as f64;
+    let u = rank_sum_b - (n2 * (n2 + 1)) as f64 / 2.0;
+    let p_value = if tie_term == 0.0 && n1 * n2 <= EXACT_MAX_PAIRS {
+        exact_p_value(n1, n2, u)
+    } else {
+        let n = (n1 + n2) as f64;
+        let variance = pairs / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+        if variance <= 0.0 {
+            1.0
+        } else {
+            let distance = ((u - pairs / 2.0).abs() - 0.5).max(0.0);
+            (2.0 * normal_sf(distance / variance.sqrt())).min(1.0)
+        }
+    };
+    MannWhitney { u, p_value }
+}
+
+/// Two-sided p-value from the exact null distribution of U.
+fn exact_p_value(n1: usize, n2: usize, u: f64) -> f64 {
+    let max_u = n1 * n2;
+    // counts[m][k] = orderings of m values from `a` and `n` from `b` with U = k, built up over `n`.
+    let mut counts: Vec<Vec<f64>> = (0..=n1)
+        .map(|_| {
+            let mut row = vec![0.0; max_u + 1];
+            row[0] = 1.0;
+            row
+        })
+        .collect();
+    for n in 1..=n2 {
+        let mut next = vec![vec![0.0; max_u + 1]; n1 + 1];
+        next[0][0] = 1.0;
+        for m in 1..=n1 {
+            for k in 0..=m * n {
+                // The largest value comes from `b` (beating all m values of `a`) or from `a`.
+                let from_b = if k >= m { counts[m][k - m] } else { 0.0 };
+                next[m][k] = from_b + next[m - 1][k];
+            }
+        }
+        counts = next;
+    }
+
+    let dist = &counts[n1];
+    let total: f64 = dist.iter().sum();
+    let u = u.round() as usize;
+    let lower: f64 = dist[..=u].iter().sum();
+    let upper: f64 = dist[u..].iter().sum();
+    (2.0 * lower.min(upper) / total).min(1.0)
+}
+
+/// Benjamini-Hochberg adjusted p-values, in the order of `p_values`, controlling the false
+/// discovery rate across all of them.
+pub fn benjamini_hochberg(p_values: &[f64]) -> Vec<f64> {
+    let m = p_values.len();
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|a, b| p_values[*a].total_cmp(&p_values[*b]));
+
+    let mut adjusted = vec![0.0; m];
+    let mut running_min: f64 = 1.0;
+    for (rank, index) in order.iter().enumerate().rev() {
+        let scaled = p_values[*index] * m as f64 / (rank + 1) as f64;
+        running_min = running_min.min(scaled);
+        adjusted[*index] = running_min;
+    }
+    adjusted
+}
+
+/// Standard normal survival function, `P(Z > z)`.
+fn normal_sf(z: f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Numerical Recipes' Chebyshev fit, relative error < 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Cliff's delta from a Mann-Whitney U: `P(b > a) - P(b < a)`, in `[-1, 1]`.
+pub fn cliffs_delta(u: f64, n1: usize, n2: usize) -> f64 {
+    if n1 == 0 || n2 == 0 {
+        return 0.0;
+    }
+    2.0 * u / (n1 * n2) as f64 - 1.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Magnitude {
+    Negligible,
+    Small,
+    Medium,
+    Large,
+}
+
+impl Magnitude {
+    /// Conventional thresholds for Cliff's delta (Romano et al.).
+    pub fn of(delta: f64) -> Self {
+        match delta.abs() {
+            d if d < 0.147 => Magnitude::Negligible,
+            d if d < 0.33 => Magnitude::Small,
+            d if d < 0.474 => Magnitude::Medium,
+            _ => Magnitude::Large,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Magnitude::Negligible => "negligible",
+            Magnitude::Small => "small",
+            Magnitude::Medium => "medium",
+            Magnitude::Large => "large",
+        }
+    }
+}
+
+/// SplitMix64: a small, seedable generator so bootstrap results are reproducible.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index in `0..n`; `n` must be non-zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+/// Median of `values`, which must be sorted ascending.
+pub fn median(values: &[f64]) -> f64 {
+    percentile(values, 0.5, PercentileMethod::Linear)
+}
+
+/// Percentile bootstrap confidence interval for `median(b) - median(a)`.
+pub fn bootstrap_median_shift(
+    a: &[f64],
+    b: &[f64],
+    resamples: usize,
+    confidence: f64,
+    rng: &mut Rng,
+) -> (f64, f64) {
+    if a.is_empty() || b.is_empty() || resamples == 0 {
+        return (0.0, 0.0);
+    }
+
+    let mut resample = |values: &[f64], buf: &mut Vec<f64>| {
+        buf.clear();
+        buf.extend((0..values.len()).map(|_| values[rng.below(values.len())]));
+        buf.sort_by(f64::total_cmp);
+        median(buf)
+    };
+    let (mut buf_a, mut buf_b) = (Vec::with_capacity(a.len()), Vec::with_capacity(b.len()));
+    let mut shifts: Vec<f64> = (0..resamples)
+        .map(|_| resample(b, &mut buf_b) - resample(a, &mut buf_a))
+        .collect();
+    shifts.sort_by(f64::total_cmp);
+
+    let tail = (1.0 - confidence) / 2.0;
+    (
+        percentile(&shifts, tail, PercentileMethod::Linear),
+        percentile(&shifts, 1.0 - tail, PercentileMethod::Linear),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn mann_whitney_matches_reference_values() {
+        // Exact: complete separation of 3 vs 3 gives the smallest two-sided p of 2/20.
+        let result = mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]);
+        assert_eq!(result.u, 9.0);
+        assert!(close(result.p_value, 0.1), "{}", result.p_value);
+
+        // Exact, partial overlap: 20 of 25 pairs favour `b`.
+        let a = [1.1, 2.3, 3.2, 4.8, 5.0];
+        let b = [2.9, 4.1, 5.5, 6.0, 7.2];
+        let result = mann_whitney_u(&a, &b);
+        assert_eq!(result.u, 20.0);
+        assert!(close(result.p_value, 0.150794), "{}", result.p_value);
+
+        // Ties fall back to the normal approximation with tie and continuity correction.
+        let a = [10.0, 10.0, 11.0, 12.0, 12.0, 13.0, 14.0];
+        let b = [12.0, 13.0, 14.0, 14.0, 15.0, 16.0, 16.0];
+        let result = mann_whitney_u(&a, &b);
+        assert_eq!(result.u, 43.5);
+        assert!(close(result.p_value, 0.016714), "{}", result.p_value);
+
+        let same = mann_whitney_u(&[5.0; 4], &[5.0; 4]);
+        assert_eq!(same.p_value, 1.0);
+        assert_eq!(mann_whitney_u(&[], &[1.0]).p_value, 1.0);
+    }
+
+    #[test]
+    fn benjamini_hochberg_matches_reference_values() {
+        // Reference values from R's p.adjust(p, "BH").
+        let adjusted = benjamini_hochberg(&[0.01, 0.04, 0.03, 0.005, 0.2]);
+        let expected = [0.025, 0.05, 0.05, 0.025, 0.2];
+        for (a, e) in adjusted.iter().zip(expected) {
+            assert!(close(*a, e), "{:?}", adjusted);
+        }
+        assert_eq!(benjamini_hochberg(&[0.9, 0.8]), vec![0.9, 0.9]);
+        assert!(benjamini_hochberg(&[]).is_empty());
+    }
+
+    #[test]
+    fn effect_size_and_magnitude() {
+        assert_eq!(cliffs_delta(9.0, 3, 3), 1.0);
+        assert_eq!(cliffs_delta(0.0, 3, 3), -1.0);
+        assert_eq!(cliffs_delta(4.5, 3, 3), 0.0);
+        assert_eq!(Magnitude::of(0.1), Magnitude::Negligible);
+        assert_eq!(Magnitude::of(-0.2), Magnitude::Small);
+        assert_eq!(Magnitude::of(0.4), Magnitude::Medium);
+        assert_eq!(Magnitude::of(-0.9), Magnitude::Large);
+    }
+
+    #[test]
+    fn bootstrap_is_seeded_and_brackets_the_shift() {
+        let a: Vec<f64> = (0..20).map(|i| 100.0 + (i % 7) as f64).collect();
+        let b: Vec<f64> = a.iter().map(|v| v + 50.0).collect();
+
+        let ci = bootstrap_median_shift(&a, &b, 500, 0.95, &mut Rng::new(7));
+        assert!(ci.0 <= 50.0 && 50.0 <= ci.1, "{:?}", ci);
+        assert!(ci.0 > 40.0 && ci.1 < 60.0, "{:?}", ci);
+        assert_eq!(
+            ci,
+            bootstrap_median_shift(&a, &b, 500, 0.95, &mut Rng::new(7))
+        );
+
+        let mut rng = Rng::new(1);
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+        assert_eq!(median(&[1.0, 2.0, 3.0, 10.0]), 2.5);
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::har::HarEntry;
+use crate::report::{GroupBy, GroupRow, Report, ReportOptions};
+
+/// How one test (input file) used one endpoint.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TestUsage {
+    pub test: String,
+    pub requests: usize,
+    pub total_time_ms: f64,
+    pub max_time_ms: f64,
+}
+
+/// Cross-file index of which tests hit which endpoint.
+#[derive(Debug, Default)]
+pub struct SuiteIndex {
+    endpoints: HashMap<String, HashMap<String, TestUsage>>,
+}
+
+impl SuiteIndex {
+    pub fn push(&mut self, test: &str, entry: &HarEntry, opts: &ReportOptions) {
+        let usage = self
+            .endpoints
+            .entry(GroupBy::Endpoint.key(entry, opts))
+            .or_default()
+            .entry(test.to_string())
+            .or_insert_with(|| TestUsage {
+                test: test.to_string(),
+                ..Default::default()
+            });
+        usage.requests += 1;
+        usage.total_time_ms += entry.time;
+        usage.max_time_ms = usage.max_time_ms.max(entry.time);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuiteEndpoint {
+    #[serde(flatten)]
+    pub group: GroupRow,
+    /// Tests that requested the endpoint, by name.
+    pub tests: Vec<TestUsage>,
+    /// The test with the slowest single request to the endpoint.
+    pub worst_test: Option<TestUsage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Suite {
+    pub tests: usize,
+    pub report: Report,
+    pub endpoints: Vec<SuiteEndpoint>,
+}
+
+/// Joins the endpoint groups of a merged report with the per-test index.
+///
+/// `report` must be grouped by endpoint only.
+pub fn summarize(mut report: Report, mut index: SuiteIndex, top: Option<usize>) -> Suite {
+    let mut groups = std::mem::take(&mut report.top_groups);
+    if let Some(top) = top {
+        groups.truncate(top);
+    }
+    let endpoints = groups
+        .into_iter()
+        .map(|group| {
+            let mut tests: Vec<TestUsage> = index
+                .endpoints
+                .remove(&group.key)
+                .unwrap_or_default()
+                .into_values()
+                .collect();
+            tests.sort_by(|a, b| a.test.cmp(&b.test));
+            let worst_test = tests
+                .iter()
+                .max_by(|a, b| {
+                    a.max_time_ms
+                        .total_cmp(&b.max_time_ms)
+                        .then_with(|| b.test.cmp(&a.test))
+                })
+                .cloned();
+            SuiteEndpoint {
+                group,
+                tests,
+                worst_test,
+            }
+        })
+        .collect();
+
+    Suite {
+        tests: report.sources.len(),
+        report,
+        endpoints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::HarRequest;
+    use crate::report::{GroupSpec, ReportBuilder};
+
+    fn entry(url: &str, time: f64) -> HarEntry {
+        HarEntry {
+            time,
+            request: HarRequest {
+                url: url.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn indexes_endpoints_by_test() {
+        let opts = ReportOptions {
+            group_by: Some(GroupSpec(vec![GroupBy::Endpoint])),
+            all_groups: true,
+            percentiles: vec![50.0],
+            ..Default::default()
+        };
+        let mut builder = ReportBuilder::new(opts.clone());
+        let mut index = SuiteIndex::default();
+        let tests = [
+            (
+                "login.har",
+                vec![("https://a.test/users/1", 100.0), ("https://a.test/", 20.0)],
+            ),
+            (
+                "cart.har",
+                vec![
+                    ("https://a.test/users/2", 300.0),
+                    ("https://a.test/users/3", 50.0),
+                ],
+            ),
+            ("search.har", vec![("https://a.test/", 10.0)]),
+        ];
+        for (test, entries) in &tests {
+            builder.set_source(test);
+            for (url, time) in entries {
+                let entry = entry(url, *time);
+                builder.push(&entry);
+                index.push(test, &entry, &opts);
+            }
+        }
+
+        let suite = summarize(builder.finish(), index, None);
+        assert_eq!(suite.tests, 3);
+        assert_eq!(suite.endpoints.len(), 2);
+
+        let users = &suite.endpoints[0];
+        assert_eq!(users.group.key, "/users/{id}");
+        assert_eq!(users.group.count, 3);
+        let names: Vec<&str> = users.tests.iter().map(|t| t.test.as_str()).collect();
+        assert_eq!(names, vec!["cart.har", "login.har"]);
+        assert_eq!(users.tests[0].requests, 2);
+        assert_eq!(users.tests[0].total_time_ms, 350.0);
+        let worst = users.worst_test.as_ref().unwrap();
+        assert_eq!(
+            (worst.test.as_str(), worst.max_time_ms),
+            ("cart.har", 300.0)
+        );
+
+        let root = &suite.endpoints[1];
+        assert_eq!(root.group.key, "/");
+        assert_eq!(root.worst_test.as_ref().unwrap().test, "login.har");
+    }
+}
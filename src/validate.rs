@@ -0,0 +1,851 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::spanned::{Json, ParseError, Position, ReadChars, Spanned, Stream};
+
+/// Allowed difference between an entry's `time` and the sum of its timings, in ms.
+const TIMING_TOLERANCE_MS: f64 = 1.0;
+
+/// A single spec violation, located by JSON path and source position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Problem {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Validation result for one HAR input.
+#[derive(Debug, Serialize)]
+pub struct Validation {
+    pub source: String,
+    pub valid: bool,
+    pub problems: Vec<Problem>,
+}
+
+impl Validation {
+    pub fn new(source: &str, problems: Vec<Problem>) -> Self {
+        Self {
+            source: source.to_string(),
+            valid: problems.is_empty(),
+            problems,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Object,
+    Array,
+    String,
+    Number,
+    Integer,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Object => "object",
+            Kind::Array => "array",
+            Kind::String => "string",
+            Kind::Number => "number",
+            Kind::Integer => "integer",
+        }
+    }
+
+    fn matches(self, value: &Json) -> bool {
+        match (self, value) {
+            (Kind::Object, Json::Object(_))
+            | (Kind::Array, Json::Array(_))
+            | (Kind::String, Json::String(_))
+            | (Kind::Number, Json::Number(_)) => true,
+            (Kind::Integer, Json::Number(n)) => n.fract() == 0.0,
+            _ => false,
+        }
+    }
+}
+
+/// Checks a HAR document against the HAR 1.2 spec and returns every problem found, in
+/// source order.
+pub fn validate(text: &str) -> Vec<Problem> {
+    into_problems(check(&mut Stream::new(text.chars())))
+}
+
+/// Like [`validate`], but reads the document incrementally so only one entry is held in memory
+/// at a time. Fails only when reading does.
+pub fn validate_reader<R: BufRead>(reader: R) -> io::Result<Vec<Problem>> {
+    let mut chars = ReadChars::new(reader);
+    let mut stream = Stream::new(&mut chars);
+    let result = check(&mut stream);
+    let stopped_at = stream.position();
+    if let Some(err) = chars.io_error.take() {
+        return Err(err);
+    }
+    if chars.invalid_utf8 {
+        return Ok(vec![Problem {
+            path: "$".to_string(),
+            line: stopped_at.line,
+            column: stopped_at.column,
+            message: "file is not valid UTF-8".to_string(),
+        }]);
+    }
+    Ok(into_problems(result))
+}
+
+fn into_problems(result: Result<Vec<Problem>, ParseError>) -> Vec<Problem> {
+    match result {
+        Ok(mut problems) => {
+            problems.sort_by_key(|p| (p.line, p.column));
+            problems
+        }
+        Err(err) => vec![Problem {
+            path: "$".to_string(),
+            line: err.pos.line,
+            column: err.pos.column,
+            message: format!("invalid JSON: {}", err.message),
+        }],
+    }
+}
+
+/// Walks the document; a syntax error anywhere replaces the problems found so far.
+fn check<I: Iterator<Item = char>>(stream: &mut Stream<I>) -> Result<Vec<Problem>, ParseError> {
+    let mut checker = Checker::default();
+    let root = stream.position();
+    if stream.peek() == Some('{') {
+        stream.enter()?;
+        let mut saw_log = false;
+        while let Some(key) = stream.next_key()? {
+            if key == "log" && !saw_log {
+                saw_log = true;
+                checker.log(stream, "$.log")?;
+            } else {
+                stream.skip()?;
+            }
+        }
+        if !saw_log {
+            checker.report_at("$.log", root, "missing required field \"log\"");
+        }
+    } else {
+        let found = stream.skip()?;
+        checker.mismatch("$", root, Kind::Object, found);
+    }
+    stream.finish()?;
+    Ok(checker.problems)
+}
+
+/// Ids of `log.pages`, and the pagerefs met before the pages were read.
+#[derive(Default)]
+struct PageRefs {
+    ids: Option<HashSet<String>>,
+    pending: Vec<(String, Position, String)>,
+}
+
+#[derive(Default)]
+struct Checker {
+    problems: Vec<Problem>,
+}
+
+impl Checker {
+    fn report(&mut self, path: &str, node: &Spanned, message: impl Into<String>) {
+        self.report_at(path, node.pos, message);
+    }
+
+    fn report_at(&mut self, path: &str, pos: Position, message: impl Into<String>) {
+        self.problems.push(Problem {
+            path: path.to_string(),
+            line: pos.line,
+            column: pos.column,
+            message: message.into(),
+        });
+    }
+
+    fn mismatch(&mut self, path: &str, pos: Position, kind: Kind, found: &str) {
+        self.report_at(
+            path,
+            pos,
+            format!("expected {}, found {}", kind.name(), found),
+        );
+    }
+
+    fn expect(&mut self, path: &str, node: &Spanned, kind: Kind) -> bool {
+        let ok = kind.matches(&node.value);
+        if !ok {
+            let found = match &node.value {
+                Json::Number(_) if matches!(kind, Kind::Integer) => "non-integer number",
+                other => other.type_name(),
+            };
+            self.mismatch(path, node.pos, kind, found);
+        }
+        ok
+    }
+
+    /// Looks up `key` on `parent`, reporting it when missing (if `required`) or of the wrong
+    /// type. Optional fields may be `null`.
+    fn field<'a>(
+        &mut self,
+        parent_path: &str,
+        parent: &'a Spanned,
+        key: &str,
+        kind: Kind,
+        required: bool,
+    ) -> Option<&'a Spanned> {
+        let path = format!("{}.{}", parent_path, key);
+        match parent.get(key) {
+            None if required => {
+                self.report(&path, parent, format!("missing required field \"{}\"", key));
+                None
+            }
+            None => None,
+            Some(node) => self.member(&path, node, kind, required),
+        }
+    }
+
+    /// Checks the type of a member that is present; optional members may be `null`.
+    fn member<'a>(
+        &mut self,
+        path: &str,
+        node: &'a Spanned,
+        kind: Kind,
+        required: bool,
+    ) -> Option<&'a Spanned> {
+        if !required && node.value == Json::Null {
+            return None;
+        }
+        self.expect(path, node, kind).then_some(node)
+    }
+
+    fn fields(&mut self, path: &str, node: &Spanned, kind: Kind, required: bool, keys: &[&str]) {
+        for key in keys {
+            self.field(path, node, key, kind, required);
+        }
+    }
+
+    fn items<'a>(&mut self, path: &str, node: &'a Spanned) -> Vec<(String, &'a Spanned)> {
+        match &node.value {
+            Json::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (format!("{}[{}]", path, i), item))
+                .filter(|(item_path, item)| self.expect(item_path, item, Kind::Object))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Walks `log` member by member so entries are checked one at a time; the other members
+    /// are small and checked as whole values once the object ends.
+    fn log<I: Iterator<Item = char>>(
+        &mut self,
+        stream: &mut Stream<I>,
+        path: &str,
+    ) -> Result<(), ParseError> {
+        let pos = stream.position();
+        if stream.peek() != Some('{') {
+            let found = stream.skip()?;
+            self.mismatch(path, pos, Kind::Object, found);
+            return Ok(());
+        }
+        stream.enter()?;
+        let mut members = Vec::new();
+        let mut pages = PageRefs::default();
+        let mut saw_entries = false;
+        while let Some(key) = stream.next_key()? {
+            match key.as_str() {
+                "entries" if !saw_entries => {
+                    saw_entries = true;
+                    self.entries(stream, &format!("{}.entries", path), &mut pages)?;
+                }
+                "pages" if pages.ids.is_none() => {
+                    let node = stream.value()?;
+                    let pages_path = format!("{}.pages", path);
+                    let mut ids = HashSet::new();
+                    if let Some(list) = self.member(&pages_path, &node, Kind::Array, false) {
+                        for (page_path, page) in self.items(&pages_path, list) {
+                            self.page(&page_path, page, &mut ids);
+                        }
+                    }
+                    self.resolve_pagerefs(&mut pages, ids);
+                }
+                _ => members.push((key, stream.value()?)),
+            }
+        }
+
+        let log = Spanned {
+            value: Json::Object(members),
+            pos,
+        };
+        self.field(path, &log, "version", Kind::String, true);
+        if let Some(creator) = self.field(path, &log, "creator", Kind::Object, true) {
+            self.creator(&format!("{}.creator", path), creator);
+        }
+        if let Some(browser) = self.field(path, &log, "browser", Kind::Object, false) {
+            self.creator(&format!("{}.browser", path), browser);
+        }
+        self.field(path, &log, "comment", Kind::String, false);
+        if !saw_entries {
+            self.report_at(
+                &format!("{}.entries", path),
+                pos,
+                "missing required field \"entries\"",
+            );
+        }
+        if pages.ids.is_none() {
+            self.resolve_pagerefs(&mut pages, HashSet::new());
+        }
+        Ok(())
+    }
+
+    fn entries<I: Iterator<Item = char>>(
+        &mut self,
+        stream: &mut Stream<I>,
+        path: &str,
+        pages: &mut PageRefs,
+    ) -> Result<(), ParseError> {
+        let pos = stream.position();
+        if stream.peek() != Some('[') {
+            let found = stream.skip()?;
+            self.mismatch(path, pos, Kind::Array, found);
+            return Ok(());
+        }
+        stream.enter()?;
+        let mut index = 0;
+        while stream.next_element()? {
+            let entry = stream.value()?;
+            let entry_path = format!("{}[{}]", path, index);
+            if self.expect(&entry_path, &entry, Kind::Object) {
+                self.entry(&entry_path, &entry, pages);
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn resolve_pagerefs(&mut self, pages: &mut PageRefs, ids: HashSet<String>) {
+        for (path, pos, value) in std::mem::take(&mut pages.pending) {
+            if !ids.contains(&value) {
+                self.report_at(&path, pos, unknown_pageref(&value));
+            }
+        }
+        pages.ids = Some(ids);
+    }
+
+    fn creator(&mut self, path: &str, creator: &Spanned) {
+        self.fields(path, creator, Kind::String, true, &["name", "version"]);
+        self.field(path, creator, "comment", Kind::String, false);
+    }
+
+    fn page(&mut self, path: &str, page: &Spanned, page_ids: &mut HashSet<String>) {
+        self.started_date_time(path, page);
+        if let Some(id) = self.field(path, page, "id", Kind::String, true) {
+            if let Json::String(value) = &id.value {
+                if !page_ids.insert(value.clone()) {
+                    self.report(
+                        &format!("{}.id", path),
+                        id,
+                        format!("duplicate page id \"{}\"", value),
+                    );
+                }
+            }
+        }
+        self.field(path, page, "title", Kind::String, true);
+        self.field(path, page, "comment", Kind::String, false);
+        if let Some(timings) = self.field(path, page, "pageTimings", Kind::Object, true) {
+            let timings_path = format!("{}.pageTimings", path);
+            for key in ["onContentLoad", "onLoad"] {
+                if let Some(value) = self.field(&timings_path, timings, key, Kind::Number, false) {
+                    self.optional_timing(&format!("{}.{}", timings_path, key), value);
+                }
+            }
+        }
+    }
+
+    fn entry(&mut self, path: &str, entry: &Spanned, pages: &mut PageRefs) {
+        if let Some(pageref) = self.field(path, entry, "pageref", Kind::String, false) {
+            if let Json::String(value) = &pageref.value {
+                let pageref_path = format!("{}.pageref", path);
+                match &pages.ids {
+                    Some(ids) if !ids.contains(value) => {
+                        self.report(&pageref_path, pageref, unknown_pageref(value));
+                    }
+                    Some(_) => {}
+                    None => pages
+                        .pending
+                        .push((pageref_path, pageref.pos, value.clone())),
+                }
+            }
+        }
+        self.started_date_time(path, entry);
+        let time = self.field(path, entry, "time", Kind::Number, true);
+        if let Some(
+            node @ Spanned {
+                value: Json::Number(t),
+                ..
+            },
+        ) = time
+        {
+            if *t < 0.0 {
+                self.report(&format!("{}.time", path), node, "time must not be negative");
+            }
+        }
+        if let Some(request) = self.field(path, entry, "request", Kind::Object, true) {
+            self.request(&format!("{}.request", path), request);
+        }
+        if let Some(response) = self.field(path, entry, "response", Kind::Object, true) {
+            self.response(&format!("{}.response", path), response);
+        }
+        if let Some(cache) = self.field(path, entry, "cache", Kind::Object, true) {
+            self.cache(&format!("{}.cache", path), cache);
+        }
+        if let Some(timings) = self.field(path, entry, "timings", Kind::Object, true) {
+            self.timings(&format!("{}.timings", path), timings, time);
+        }
+        self.fields(
+            path,
+            entry,
+            Kind::String,
+            false,
+            &["serverIPAddress", "connection", "comment"],
+        );
+    }
+
+    fn started_date_time(&mut self, path: &str, node: &Spanned) {
+        let Some(date) = self.field(path, node, "startedDateTime", Kind::String, true) else {
+            return;
+        };
+        if let Json::String(value) = &date.value {
+            if DateTime::parse_from_rfc3339(value).is_err() {
+                self.report(
+                    &format!("{}.startedDateTime", path),
+                    date,
+                    format!(
+                        "\"{}\" is not an ISO 8601 date-time with a time zone",
+                        value
+                    ),
+                );
+            }
+        }
+    }
+
+    fn request(&mut self, path: &str, request: &Spanned) {
+        self.fields(
+            path,
+            request,
+            Kind::String,
+            true,
+            &["method", "url", "httpVersion"],
+        );
+        self.cookies(path, request);
+        self.name_values(path, request, "headers");
+        self.name_values(path, request, "queryString");
+        if let Some(post_data) = self.field(path, request, "postData", Kind::Object, false) {
+            let post_path = format!("{}.postData", path);
+            self.field(&post_path, post_data, "mimeType", Kind::String, true);
+            self.fields(
+                &post_path,
+                post_data,
+                Kind::String,
+                false,
+                &["text", "comment"],
+            );
+            if let Some(params) = self.field(&post_path, post_data, "params", Kind::Array, false) {
+                for (param_path, param) in self.items(&format!("{}.params", post_path), params) {
+                    self.field(&param_path, param, "name", Kind::String, true);
+                    self.fields(
+                        &param_path,
+                        param,
+                        Kind::String,
+                        false,
+                        &["value", "fileName", "contentType", "comment"],
+                    );
+                }
+            }
+        }
+        self.sizes(path, request, &["headersSize", "bodySize"], true);
+        self.field(path, request, "comment", Kind::String, false);
+    }
+
+    fn response(&mut self, path: &str, response: &Spanned) {
+        self.field(path, response, "status", Kind::Integer, true);
+        self.fields(
+            path,
+            response,
+            Kind::String,
+            true,
+            &["statusText", "httpVersion", "redirectURL"],
+        );
+        self.cookies(path, response);
+        self.name_values(path, response, "headers");
+        if let Some(content) = self.field(path, response, "content", Kind::Object, true) {
+            let content_path = format!("{}.content", path);
+            self.sizes(&content_path, content, &["size"], true);
+            self.sizes(&content_path, content, &["compression"], false);
+            self.field(&content_path, content, "mimeType", Kind::String, true);
+            self.fields(
+                &content_path,
+                content,
+                Kind::String,
+                false,
+                &["text", "encoding", "comment"],
+            );
+        }
+        self.sizes(path, response, &["headersSize", "bodySize"], true);
+        self.field(path, response, "comment", Kind::String, false);
+    }
+
+    fn cookies(&mut self, path: &str, parent: &Spanned) {
+        let Some(cookies) = self.field(path, parent, "cookies", Kind::Array, true) else {
+            return;
+        };
+        for (cookie_path, cookie) in self.items(&format!("{}.cookies", path), cookies) {
+            self.fields(&cookie_path, cookie, Kind::String, true, &["name", "value"]);
+            self.fields(
+                &cookie_path,
+                cookie,
+                Kind::String,
+                false,
+                &["path", "domain", "expires", "comment"],
+            );
+        }
+    }
+
+    fn name_values(&mut self, path: &str, parent: &Spanned, key: &str) {
+        let Some(list) = self.field(path, parent, key, Kind::Array, true) else {
+            return;
+        };
+        for (item_path, item) in self.items(&format!("{}.{}", path, key), list) {
+            self.fields(&item_path, item, Kind::String, true, &["name", "value"]);
+            self.field(&item_path, item, "comment", Kind::String, false);
+        }
+    }
+
+    /// Sizes are integers where `-1` means unknown; other negative values are invalid.
+    fn sizes(&mut self, path: &str, parent: &Spanned, keys: &[&str], required: bool) {
+        for key in keys {
+            if let Some(node) = self.field(path, parent, key, Kind::Integer, required) {
+                if let Json::Number(n) = node.value {
+                    if n < -1.0 {
+                        self.report(
+                            &format!("{}.{}", path, key),
+                            node,
+                            format!("negative size {}; only -1 is allowed for unknown sizes", n),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn cache(&mut self, path: &str, cache: &Spanned) {
+        for key in ["beforeRequest", "afterRequest"] {
+            if let Some(entry) = self.field(path, cache, key, Kind::Object, false) {
+                let entry_path = format!("{}.{}", path, key);
+                self.fields(
+                    &entry_path,
+                    entry,
+                    Kind::String,
+                    true,
+                    &["lastAccess", "eTag"],
+                );
+                self.field(&entry_path, entry, "hitCount", Kind::Integer, true);
+                self.fields(
+                    &entry_path,
+                    entry,
+                    Kind::String,
+                    false,
+                    &["expires", "comment"],
+                );
+            }
+        }
+        self.field(path, cache, "comment", Kind::String, false);
+    }
+
+    fn optional_timing(&mut self, path: &str, node: &Spanned) -> Option<f64> {
+        let Json::Number(value) = node.value else {
+            return None;
+        };
+        if value < 0.0 && value != -1.0 {
+            self.report(path, node, format!("{} must be -1 or non-negative", value));
+            return None;
+        }
+        (value >= 0.0).then_some(value)
+    }
+
+    fn timings(&mut self, path: &str, timings: &Spanned, time: Option<&Spanned>) {
+        let mut sum = 0.0;
+        let phase = |checker: &mut Self, key: &str, required: bool| {
+            let node = checker.field(path, timings, key, Kind::Number, required)?;
+            let phase_path = format!("{}.{}", path, key);
+            let value = if required {
+                match node.value {
+                    Json::Number(v) if v < 0.0 => {
+                        checker.report(&phase_path, node, format!("{} must not be negative", key));
+                        None
+                    }
+                    Json::Number(v) => Some(v),
+                    _ => None,
+                }
+            } else {
+                checker.optional_timing(&phase_path, node)
+            };
+            value
+        };
+
+        let blocked = phase(self, "blocked", false);
+        let dns = phase(self, "dns", false);
+        let connect = phase(self, "connect", false);
+        let ssl = phase(self, "ssl", false);
+        let send = phase(self, "send", true);
+        let wait = phase(self, "wait", true);
+        let receive = phase(self, "receive", true);
+        self.field(path, timings, "comment", Kind::String, false);
+
+        if let (Some(ssl), Some(connect)) = (ssl, connect) {
+            if ssl > connect + TIMING_TOLERANCE_MS {
+                self.report(
+                    &format!("{}.ssl", path),
+                    timings.get("ssl").unwrap_or(timings),
+                    format!("ssl {} exceeds connect {} it is part of", ssl, connect),
+                );
+            }
+        }
+
+        // ssl is included in connect, so it is not added separately.
+        for value in [blocked, dns, connect, send, wait, receive]
+            .into_iter()
+            .flatten()
+        {
+            sum += value;
+        }
+        if let Some(
+            node @ Spanned {
+                value: Json::Number(time),
+                ..
+            },
+        ) = time
+        {
+            if (sum - time).abs() > TIMING_TOLERANCE_MS {
+                let entry_path = path.trim_end_matches(".timings");
+                self.report(
+                    &format!("{}.time", entry_path),
+                    node,
+                    format!("time {} does not match the sum of timings {}", time, sum),
+                );
+            }
+        }
+    }
+}
+
+fn unknown_pageref(value: &str) -> String {
+    format!("pageref \"{}\" does not match any page id", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_har() -> serde_json::Value {
+        serde_json::json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "test", "version": "1.0" },
+                "pages": [{
+                    "startedDateTime": "2024-05-01T10:00:00.000Z",
+                    "id": "page_1",
+                    "title": "Home",
+                    "pageTimings": { "onContentLoad": 120.5, "onLoad": -1 }
+                }],
+                "entries": [{
+                    "pageref": "page_1",
+                    "startedDateTime": "2024-05-01T10:00:00.100+02:00",
+                    "time": 50.5,
+                    "request": {
+                        "method": "GET",
+                        "url": "https://example.com/",
+                        "httpVersion": "HTTP/1.1",
+                        "cookies": [],
+                        "headers": [{ "name": "Accept", "value": "*/*" }],
+                        "queryString": [],
+                        "headersSize": 120,
+                        "bodySize": 0
+                    },
+                    "response": {
+                        "status": 200,
+                        "statusText": "OK",
+                        "httpVersion": "HTTP/1.1",
+                        "cookies": [],
+                        "headers": [],
+                        "content": { "size": 1000, "mimeType": "text/html" },
+                        "redirectURL": "",
+                        "headersSize": -1,
+                        "bodySize": 1000
+                    },
+                    "cache": {},
+                    "timings": {
+                        "blocked": -1, "dns": 5, "connect": 10, "ssl": 6,
+                        "send": 0.5, "wait": 30, "receive": 5
+                    }
+                }]
+            }
+        })
+    }
+
+    fn problems(har: &serde_json::Value) -> Vec<Problem> {
+        validate(&serde_json::to_string_pretty(har).expect("serialize"))
+    }
+
+    fn messages(problems: &[Problem]) -> Vec<(String, String)> {
+        problems
+            .iter()
+            .map(|p| (p.path.clone(), p.message.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_spec_compliant_har() {
+        assert_eq!(problems(&valid_har()), []);
+    }
+
+    #[test]
+    fn reports_missing_fields_and_wrong_types() {
+        let mut har = valid_har();
+        let entry = &mut har["log"]["entries"][0];
+        entry["request"]
+            .as_object_mut()
+            .expect("object")
+            .remove("url");
+        entry["response"]["status"] = serde_json::json!("200");
+        entry["response"]["content"]["size"] = serde_json::json!(1.5);
+
+        assert_eq!(
+            messages(&problems(&har)),
+            [
+                (
+                    "$.log.entries[0].request.url".to_string(),
+                    "missing required field \"url\"".to_string()
+                ),
+                (
+                    "$.log.entries[0].response.content.size".to_string(),
+                    "expected integer, found non-integer number".to_string()
+                ),
+                (
+                    "$.log.entries[0].response.status".to_string(),
+                    "expected integer, found string".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_semantic_problems() {
+        let mut har = valid_har();
+        let entry = &mut har["log"]["entries"][0];
+        entry["pageref"] = serde_json::json!("page_9");
+        entry["startedDateTime"] = serde_json::json!("2024-05-01 10:00");
+        entry["time"] = serde_json::json!(80);
+        entry["request"]["bodySize"] = serde_json::json!(-2);
+        entry["timings"]["wait"] = serde_json::json!(-1);
+        entry["timings"]["ssl"] = serde_json::json!(20);
+
+        assert_eq!(
+            messages(&problems(&har)),
+            [
+                (
+                    "$.log.entries[0].pageref".to_string(),
+                    "pageref \"page_9\" does not match any page id".to_string()
+                ),
+                (
+                    "$.log.entries[0].request.bodySize".to_string(),
+                    "negative size -2; only -1 is allowed for unknown sizes".to_string()
+                ),
+                (
+                    "$.log.entries[0].startedDateTime".to_string(),
+                    "\"2024-05-01 10:00\" is not an ISO 8601 date-time with a time zone"
+                        .to_string()
+                ),
+                (
+                    "$.log.entries[0].time".to_string(),
+                    "time 80 does not match the sum of timings 20.5".to_string()
+                ),
+                (
+                    "$.log.entries[0].timings.ssl".to_string(),
+                    "ssl 20 exceeds connect 10 it is part of".to_string()
+                ),
+                (
+                    "$.log.entries[0].timings.wait".to_string(),
+                    "wait must not be negative".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_positions_of_problems() {
+        let text = "{\n  \"log\": {\n    \"version\": 12,\n    \"entries\": []\n  }\n}";
+        let problems = validate(text);
+        assert_eq!(
+            problems,
+            [
+                Problem {
+                    path: "$.log.creator".to_string(),
+                    line: 2,
+                    column: 10,
+                    message: "missing required field \"creator\"".to_string(),
+                },
+                Problem {
+                    path: "$.log.version".to_string(),
+                    line: 3,
+                    column: 16,
+                    message: "expected string, found number".to_string(),
+                },
+            ]
+        );
+
+        let problems = validate("{\n  \"log\": {,}\n}");
+        assert_eq!(problems[0].path, "$");
+        assert_eq!((problems[0].line, problems[0].column), (2, 11));
+        assert!(problems[0].message.starts_with("invalid JSON: "));
+    }
+
+    #[test]
+    fn resolves_pagerefs_of_entries_read_before_pages() {
+        let mut har = valid_har();
+        let mut second = har["log"]["entries"][0].clone();
+        second["pageref"] = serde_json::json!("page_9");
+        har["log"]["entries"]
+            .as_array_mut()
+            .expect("array")
+            .push(second);
+        let log = &har["log"];
+        let text = format!(
+            "{{\"log\": {{\"entries\": {}, \"pages\": {}, \"version\": \"1.2\", \"creator\": {}}}}}",
+            log["entries"], log["pages"], log["creator"]
+        );
+
+        assert_eq!(
+            messages(&validate(&text)),
+            [(
+                "$.log.entries[1].pageref".to_string(),
+                "pageref \"page_9\" does not match any page id".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn reader_matches_text_validation() {
+        let mut har = valid_har();
+        har["log"]["entries"][0]["time"] = serde_json::json!(-3);
+        let text = serde_json::to_string_pretty(&har).expect("serialize");
+        let reader = io::BufReader::with_capacity(7, text.as_bytes());
+        assert_eq!(validate_reader(reader).expect("read"), validate(&text));
+
+        let mut bytes = b"{\n  \"log\": \"\xc3\xa9".to_vec();
+        bytes.extend_from_slice(b"\xff\"\n}");
+        let problems = validate_reader(bytes.as_slice()).expect("read");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].message, "file is not valid UTF-8");
+        assert_eq!((problems[0].line, problems[0].column), (2, 12));
+    }
+}
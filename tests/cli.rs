@@ -145,3 +145,1176 @@ fn text_output_with_group_by_host_has_group_section() {
         .stdout(predicate::str::contains("cdn.example.com"))
         .stdout(predicate::str::contains("api.example.com"));
 }
+
+fn json_report(args: &[&str]) -> serde_json::Value {
+    let output = Command::cargo_bin("perf_tool")
+        .expect("binary should build")
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    serde_json::from_slice(&output).expect("must be valid JSON")
+}
+
+#[test]
+fn browser_exported_hars_report_bytes() {
+    let cases = [
+        (
+            "chrome.har",
+            150471,
+            "https://www.example.org/static/app.js?v=7f3a9c",
+            131461,
+        ),
+        ("firefox.har", 113786, "https://www.mozilla.org/en-US/", 61916),
+        (
+            "safari.har",
+            220169,
+            "https://www.apple.com/ac/globalnav/main.js",
+            121155,
+        ),
+    ];
+
+    for (name, total_bytes, largest_url, largest_bytes) in cases {
+        let fixture = fixture_path(name);
+        let report = json_report(&["--json", &fixture]);
+
+        assert_eq!(report["total_bytes"], total_bytes, "{name}");
+        assert_eq!(report["top_largest"][0]["url"], largest_url, "{name}");
+        assert_eq!(report["top_largest"][0]["bytes"], largest_bytes, "{name}");
+    }
+}
+
+#[test]
+fn chrome_har_text_output_reports_sizes() {
+    let fixture = fixture_path("chrome.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("total_bytes: 146.94 KB"))
+        .stdout(predicate::str::contains(
+            "128.38 KB  https://www.example.org/static/app.js?v=7f3a9c",
+        ));
+}
+
+#[test]
+fn json_output_has_timing_phase_breakdown() {
+    let fixture = fixture_path("chrome.har");
+    let report = json_report(&["--json", "--group-by", "host", &fixture]);
+
+    let phases = report["timing_phases"].as_array().expect("array");
+    assert_eq!(phases.len(), 7);
+    assert_eq!(phases[1]["phase"], "dns");
+    assert_eq!(phases[1]["count"], 2);
+    assert_eq!(phases[1]["total_ms"], 30.402);
+    assert_eq!(phases[5]["phase"], "wait");
+    assert_eq!(phases[5]["count"], 3);
+
+    let group_phases = report["top_groups"][1]["timing_phases"]
+        .as_array()
+        .expect("array");
+    assert_eq!(group_phases[5]["phase"], "wait");
+    assert_eq!(group_phases[5]["total_ms"], 40.9);
+}
+
+#[test]
+fn text_output_has_timing_phase_section() {
+    let fixture = fixture_path("safari.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("timing phases:"))
+        .stdout(predicate::str::contains(
+            "  dns           1        14.20      14.20      14.20      14.20",
+        ));
+}
+
+#[test]
+fn sort_by_ttfb_adds_sorted_list_with_derived_metrics() {
+    let fixture = fixture_path("chrome.har");
+    let report = json_report(&["--json", "--sort-by", "ttfb", "--top", "2", &fixture]);
+
+    assert_eq!(report["sort_by"], "ttfb");
+    let sorted = report["top_sorted"].as_array().expect("array");
+    assert_eq!(sorted.len(), 2);
+    assert_eq!(sorted[0]["url"], "https://www.example.org/");
+    assert_eq!(sorted[1]["url"], "https://api.example.org/v1/events");
+    assert_eq!(sorted[1]["download_ms"], 2.8);
+    assert!(sorted[0]["throughput_bps"].is_number());
+}
+
+#[test]
+fn sort_by_throughput_text_output() {
+    let fixture = fixture_path("chrome.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg("--sort-by")
+        .arg("throughput")
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lowest 3 by throughput:"))
+        .stdout(predicate::str::contains(
+            "89.29 KB/s  https://api.example.org/v1/events",
+        ));
+}
+
+#[test]
+fn percentiles_option_controls_group_percentile_map() {
+    let fixture = fixture_path("grouped.har");
+    let report = json_report(&[
+        "--json",
+        "--group-by",
+        "host",
+        "--percentiles",
+        "50,90",
+        "--percentile-method",
+        "linear",
+        &fixture,
+    ]);
+
+    assert_eq!(report["percentile_method"], "linear");
+    let group = &report["top_groups"][0];
+    assert_eq!(group["key"], "cdn.example.com");
+    assert_eq!(group["percentiles"]["p50"], 175.0);
+    assert_eq!(group["percentiles"]["p90"], 275.0);
+    assert_eq!(group["min_time_ms"], 50.0);
+    assert_eq!(group["max_time_ms"], 300.0);
+    assert_eq!(group["median_time_ms"], 175.0);
+    assert_eq!(group["stddev_time_ms"], 125.0);
+}
+
+#[test]
+fn invalid_percentile_is_rejected() {
+    let fixture = fixture_path("sample.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg("--percentiles")
+        .arg("150")
+        .arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("percentile must be in (0, 100]"));
+}
+
+#[test]
+fn group_by_status_class_uses_response_status() {
+    let fixture = fixture_path("chrome.har");
+    let report = json_report(&["--json", "--group-by", "status-class", &fixture]);
+
+    assert_eq!(report["group_by"], "status-class");
+    let groups = report["top_groups"].as_array().expect("array");
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["key"], "2xx");
+    assert_eq!(groups[0]["count"], 3);
+}
+
+#[test]
+fn text_output_labels_group_section_with_dimension() {
+    let fixture = fixture_path("chrome.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg("--group-by")
+        .arg("method")
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("groups by method (top 2):"))
+        .stdout(predicate::str::contains("GET"))
+        .stdout(predicate::str::contains("POST"));
+}
+
+#[test]
+fn group_by_multiple_dimensions_produces_composite_keys() {
+    let fixture = fixture_path("chrome.har");
+    let report = json_report(&["--json", "--group-by", "host,method", &fixture]);
+
+    assert_eq!(report["group_by"], "host,method");
+    let groups = report["top_groups"].as_array().expect("array");
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0]["key"], "www.example.org | GET");
+    assert_eq!(groups[0]["dimensions"]["host"], "www.example.org");
+    assert_eq!(groups[0]["dimensions"]["method"], "GET");
+    assert_eq!(groups[1]["key"], "api.example.org | POST");
+}
+
+#[test]
+fn first_party_option_reports_third_party_share() {
+    let fixture = fixture_path("third_party.har");
+    let report = json_report(&[
+        "--json",
+        "--group-by",
+        "domain",
+        "--first-party",
+        "shop.co.uk",
+        &fixture,
+    ]);
+
+    assert_eq!(report["third_party"]["requests"], 2);
+    assert_eq!(report["third_party"]["time_ms"], 100.0);
+    assert_eq!(report["third_party"]["bytes"], 300);
+    assert_eq!(report["third_party"]["time_share"], 0.2);
+
+    let groups = report["top_groups"].as_array().expect("array");
+    assert_eq!(groups[0]["key"], "shop.co.uk");
+    assert_eq!(groups[0]["count"], 2);
+    assert_eq!(groups[0]["party"], "first-party");
+    assert_eq!(groups[1]["key"], "google-analytics.com");
+    assert_eq!(groups[1]["party"], "third-party");
+    assert_eq!(groups[2]["key"], "doubleclick.net");
+    assert_eq!(report["top_slowest"][0]["party"], "first-party");
+}
+
+#[test]
+fn text_output_tags_third_party_requests() {
+    let fixture = fixture_path("third_party.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg("--first-party")
+        .arg("shop.co.uk")
+        .arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "third_party: 2 req (50.0%), 100.00 ms (20.0%), 300 B (15.0%)",
+        ))
+        .stdout(predicate::str::contains(
+            "https://www.google-analytics.com/g/collect?v=2  [third-party]",
+        ));
+}
+
+#[test]
+fn group_by_endpoint_normalizes_ids_and_applies_templates() {
+    let fixture = fixture_path("api.har");
+    let templates = fixture_path("endpoint_templates.json");
+    let report = json_report(&[
+        "--json",
+        "--group-by",
+        "endpoint",
+        "--endpoint-method",
+        "--endpoint-templates",
+        &templates,
+        &fixture,
+    ]);
+
+    assert_eq!(report["group_by"], "endpoint");
+    let groups = report["top_groups"].as_array().expect("array");
+    let keys: Vec<&str> = groups
+        .iter()
+        .map(|g| g["key"].as_str().expect("key"))
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "POST /users/{id}/orders",
+            "GET /users/{id}/orders/{uuid}",
+            "GET /users/{username}/profile",
+            "GET /users/{id}/orders",
+        ]
+    );
+    assert_eq!(groups[1]["count"], 2);
+    assert_eq!(groups[2]["count"], 2);
+}
+
+#[test]
+fn missing_endpoint_templates_file_fails() {
+    let fixture = fixture_path("api.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg("--group-by")
+        .arg("endpoint")
+        .arg("--endpoint-templates")
+        .arg("tests/fixtures/missing-templates.json")
+        .arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "failed to read endpoint templates",
+        ));
+}
+
+#[test]
+fn invalid_urls_are_reported_as_diagnostics() {
+    let fixture = fixture_path("grouped.har");
+    let report = json_report(&["--json", &fixture]);
+
+    let errors = report["url_errors"].as_array().expect("array");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["url"], "not-a-valid-url");
+    assert_eq!(errors[0]["error"], "relative");
+    assert_eq!(errors[0]["message"], "relative URL without a base");
+    assert_eq!(errors[0]["count"], 1);
+    assert_eq!(errors[0]["first_entry"], 4);
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg(&fixture)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("invalid urls (1):"))
+        .stdout(predicate::str::contains(
+            "relative URL without a base: not-a-valid-url",
+        ));
+}
+
+#[test]
+fn large_har_is_streamed_entry_by_entry() {
+    let entries: Vec<String> = (0..5000)
+        .map(|i| {
+            format!(
+                r#"{{"time": {i}, "request": {{"method": "GET", "url": "https://example.com/{i}"}},
+                "response": {{"status": 200, "content": {{"size": 10, "mimeType": "text/plain",
+                "text": "{}"}}}}}}"#,
+                "x".repeat(100)
+            )
+        })
+        .collect();
+    let har = format!(r#"{{"log": {{"entries": [{}]}}}}"#, entries.join(","));
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("large.har");
+    std::fs::write(&path, har).expect("write HAR");
+
+    let report = json_report(&[
+        "--json",
+        "--top",
+        "2",
+        "--group-by",
+        "host",
+        path.to_str().expect("utf-8 path"),
+    ]);
+    assert_eq!(report["entries"], 5000);
+    assert_eq!(report["total_bytes"], 50_000);
+    assert_eq!(report["top_slowest"][0]["url"], "https://example.com/4999");
+    assert_eq!(report["top_slowest"][1]["url"], "https://example.com/4998");
+    assert_eq!(report["top_groups"][0]["count"], 5000);
+    assert_eq!(report["top_groups"][0]["max_time_ms"], 4999.0);
+}
+
+#[test]
+fn compressed_har_matches_plain_report() {
+    let plain = fixture_path("sample.har");
+    let expected = json_report(&["--json", &plain]).to_string();
+    for name in ["sample.har.gz", "sample.har.zst"] {
+        let path = fixture_path(name);
+        // Only the source names differ.
+        let report = json_report(&["--json", &path]).to_string();
+        assert_eq!(report.replace(&path, &plain), expected, "{name}");
+    }
+}
+
+#[test]
+fn zip_bundle_reports_every_har_inside() {
+    let report = json_report(&["--json", &fixture_path("bundle.zip")]);
+    assert_eq!(report["entries"], 8);
+    assert_eq!(report["total_bytes"], 5372);
+}
+
+#[test]
+fn zip_without_hars_is_an_error() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("empty.zip");
+    let file = std::fs::File::create(&path).expect("create zip");
+    let mut zip = zip::ZipWriter::new(file);
+    zip.start_file("notes.txt", zip::write::SimpleFileOptions::default())
+        .expect("start file");
+    std::io::Write::write_all(&mut zip, b"no hars here").expect("write");
+    zip.finish().expect("finish zip");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no .har files in zip archive"));
+}
+
+#[test]
+fn reads_har_from_stdin() {
+    let har = std::fs::read(fixture_path("sample.har.gz")).expect("fixture");
+    let output = Command::cargo_bin("perf_tool")
+        .expect("binary should build")
+        .args(["--json", "-"])
+        .write_stdin(har)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).expect("valid JSON");
+    assert_eq!(report["entries"], 4);
+    assert_eq!(report["sources"], serde_json::json!(["<stdin>"]));
+}
+
+#[test]
+fn multiple_inputs_are_reported_separately_by_default() {
+    let sample = fixture_path("sample.har");
+    let third_party = fixture_path("third_party.har");
+    let reports = json_report(&["--json", &sample, &third_party]);
+
+    let reports = reports.as_array().expect("one report per file");
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0]["sources"], serde_json::json!([sample]));
+    assert_eq!(reports[0]["entries"], 4);
+    assert_eq!(reports[1]["sources"], serde_json::json!([third_party]));
+    assert_eq!(reports[1]["entries"], 4);
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args([&sample, &third_party])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("== {} ==", sample)))
+        .stdout(predicate::str::contains(format!("== {} ==", third_party)));
+}
+
+#[test]
+fn merged_inputs_tag_rows_with_their_source() {
+    let sample = fixture_path("sample.har");
+    let third_party = fixture_path("third_party.har");
+    let report = json_report(&["--json", "--merge", "--top", "1", &sample, &third_party]);
+
+    assert_eq!(report["entries"], 8);
+    assert_eq!(
+        report["sources"],
+        serde_json::json!([sample.clone(), third_party])
+    );
+    assert_eq!(report["top_slowest"][0]["url"], "https://example.com/a");
+    assert_eq!(report["top_slowest"][0]["source"], sample.as_str());
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["--merge", "--top", "1", &sample, &third_party])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "https://example.com/a  ({})",
+            sample
+        )));
+}
+
+#[test]
+fn directories_and_globs_expand_to_har_files() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    for name in ["sample.har", "third_party.har"] {
+        std::fs::copy(fixture_path(name), dir.path().join(name)).expect("copy fixture");
+    }
+    std::fs::write(dir.path().join("notes.txt"), "not a HAR").expect("write");
+
+    let dir_arg = dir.path().to_str().expect("utf-8 path");
+    let report = json_report(&["--json", "--merge", dir_arg]);
+    assert_eq!(report["entries"], 8);
+    assert_eq!(report["sources"].as_array().map(Vec::len), Some(2));
+
+    let pattern = dir.path().join("s*.har");
+    let report = json_report(&["--json", pattern.to_str().expect("utf-8 path")]);
+    assert_eq!(report["entries"], 4);
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg(dir.path().join("*.json"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no files match pattern"));
+}
+
+#[test]
+fn validate_accepts_browser_exports() {
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["validate", "--strict"])
+        .args(["chrome.har", "firefox.har", "safari.har"].map(fixture_path))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "{}: ok",
+            fixture_path("chrome.har")
+        )));
+}
+
+#[test]
+fn validate_reports_problems_with_paths_and_positions() {
+    let fixture = fixture_path("invalid.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["validate", &fixture])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{}: 4 problems", fixture)))
+        .stdout(predicate::str::contains(
+            "15:20  $.log.entries[0].pageref  pageref \"page_2\" does not match any page id",
+        ))
+        .stdout(predicate::str::contains(
+            "17:17  $.log.entries[0].time  time 100 does not match the sum of timings 60",
+        ));
+
+    let results = json_report(&["validate", "--json", &fixture]);
+    let problems = results[0]["problems"].as_array().expect("problems");
+    assert_eq!(results[0]["valid"], false);
+    assert_eq!(problems.len(), 4);
+    assert_eq!(
+        problems[2]["path"],
+        "$.log.entries[0].response.content.size"
+    );
+    assert_eq!(problems[2]["line"], 34);
+    assert_eq!(problems[3]["path"], "$.log.entries[1].startedDateTime");
+}
+
+#[test]
+fn validate_strict_fails_on_problems() {
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["validate", "--strict", &fixture_path("invalid.har")])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("validation failed: 4 problems"));
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["validate", "--strict", "-"])
+        .write_stdin(r#"{"log": {"entries": [}}"#)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "1:22  $  invalid JSON: expected value, found '}'",
+        ));
+}
+
+#[test]
+fn lenient_mode_skips_malformed_entries() {
+    let fixture = fixture_path("corrupted.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.arg(&fixture)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse HAR JSON"));
+
+    let report = json_report(&["--json", "--lenient", &fixture]);
+    assert_eq!(report["entries"], 2);
+    assert_eq!(report["total_bytes"], 1300);
+    let skipped = report["skipped_entries"].as_array().expect("array");
+    assert_eq!(skipped.len(), 2);
+    assert_eq!(skipped[0]["index"], 1);
+    assert_eq!(skipped[0]["reason"], "invalid type: null, expected f64");
+    assert_eq!(skipped[0]["source"], fixture.as_str());
+    assert_eq!(skipped[1]["index"], 3);
+    assert_eq!(skipped[1]["reason"], "missing field `request`");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["--lenient", &fixture])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("skipped entries (2):"))
+        .stdout(predicate::str::contains(
+            "entry 1     invalid type: null, expected f64",
+        ));
+}
+
+#[test]
+fn filters_are_applied_before_aggregation() {
+    let fixture = fixture_path("chrome.har");
+
+    let report = json_report(&["--json", "--host", "api.example.org", &fixture]);
+    assert_eq!(report["entries"], 1);
+    assert_eq!(report["excluded_entries"], 2);
+    assert_eq!(
+        report["top_slowest"][0]["url"],
+        "https://api.example.org/v1/events"
+    );
+
+    let report = json_report(&[
+        "--json",
+        "--status",
+        "2xx,500",
+        "--method",
+        "get",
+        "--mime",
+        "application/*",
+        "--url-regex",
+        "static/",
+        &fixture,
+    ]);
+    assert_eq!(report["entries"], 1);
+    assert_eq!(
+        report["top_slowest"][0]["url"],
+        "https://www.example.org/static/app.js?v=7f3a9c"
+    );
+    assert_eq!(
+        report["filters"]["statuses"],
+        serde_json::json!(["2xx", "500"])
+    );
+    assert_eq!(report["filters"]["methods"], serde_json::json!(["get"]));
+    assert_eq!(report["filters"]["url_regex"], "static/");
+    assert_eq!(report["filters"]["min_bytes"], serde_json::Value::Null);
+
+    let report = json_report(&["--json", "--exclude-url-regex", r"\.js", &fixture]);
+    assert_eq!(report["entries"], 2);
+
+    let report = json_report(&["--json", "--min-time", "100", "--min-bytes", "1", &fixture]);
+    assert_eq!(report["entries"], 2);
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["--page", "page_2", &fixture])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("entries: 0"))
+        .stdout(predicate::str::contains("excluded_entries: 3"));
+}
+
+#[test]
+fn time_window_is_relative_to_first_entry() {
+    let fixture = fixture_path("chrome.har");
+
+    let report = json_report(&["--json", "--until", "300ms", &fixture]);
+    assert_eq!(report["entries"], 2);
+    assert_eq!(report["filters"]["until_ms"], 300.0);
+
+    let report = json_report(&["--json", "--since", "0.5s", &fixture]);
+    assert_eq!(report["entries"], 1);
+    assert_eq!(
+        report["top_slowest"][0]["url"],
+        "https://api.example.org/v1/events"
+    );
+}
+
+#[test]
+fn time_window_does_not_depend_on_key_order() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let entries = r#""entries": [
+        { "startedDateTime": "2025-03-04T10:00:01.000Z", "time": 10,
+          "request": { "url": "https://a.test/first" }, "response": {} },
+        { "startedDateTime": "2025-03-04T10:00:01.800Z", "time": 10,
+          "request": { "url": "https://a.test/late" }, "response": {} }
+    ]"#;
+    let pages = r#""pages": [{ "id": "page_1", "startedDateTime": "2025-03-04T10:00:00.000Z" }]"#;
+    for (name, har) in [
+        (
+            "pages-first.har",
+            format!("{{ \"log\": {{ {}, {} }} }}", pages, entries),
+        ),
+        (
+            "entries-first.har",
+            format!("{{ \"log\": {{ {}, {} }} }}", entries, pages),
+        ),
+    ] {
+        let path = dir.path().join(name);
+        std::fs::write(&path, har).expect("write HAR");
+
+        // Measured from the first entry, not from the earlier page start.
+        let report = json_report(&["--json", "--until", "1s", &path.to_string_lossy()]);
+        assert_eq!(report["entries"], 2, "{}", name);
+    }
+}
+
+#[test]
+fn invalid_filter_values_are_rejected() {
+    for args in [
+        ["--status", "6xx"],
+        ["--since", "soon"],
+        ["--url-regex", "("],
+    ] {
+        let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+        cmd.args(args)
+            .arg(fixture_path("chrome.har"))
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid"));
+    }
+}
+
+#[test]
+fn where_expression_filters_entries() {
+    let fixture = fixture_path("chrome.har");
+
+    let report = json_report(&[
+        "--json",
+        "--where",
+        "response.status >= 500 or (host ~ '^api\\.' and request.method == 'POST')",
+        &fixture,
+    ]);
+    assert_eq!(report["entries"], 1);
+    assert_eq!(report["excluded_entries"], 2);
+    assert_eq!(
+        report["top_slowest"][0]["url"],
+        "https://api.example.org/v1/events"
+    );
+    assert_eq!(
+        report["filters"]["where"],
+        "response.status >= 500 or (host ~ '^api\\.' and request.method == 'POST')"
+    );
+
+    let report = json_report(&[
+        "--json",
+        "--where",
+        "not (mime ~ 'javascript') && pageref == 'page_1'",
+        &fixture,
+    ]);
+    assert_eq!(report["entries"], 2);
+}
+
+#[test]
+fn where_expression_matches_bodies_with_keep_content_text() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("bodies.har");
+    let entry = |url: &str, body: &str| {
+        format!(
+            r#"{{ "time": 10, "request": {{ "url": "{}" }},
+                 "response": {{ "content": {{ "mimeType": "application/json", "text": "{}" }} }} }}"#,
+            url, body
+        )
+    };
+    let har = format!(
+        r#"{{ "log": {{ "entries": [{}, {}] }} }}"#,
+        entry("https://a.test/ok", "{\\\"ok\\\":true}"),
+        entry("https://a.test/err", "{\\\"error\\\":\\\"quota\\\"}")
+    );
+    std::fs::write(&path, har).expect("write HAR");
+    let path = path.to_string_lossy();
+
+    let filter = "response.content.text ~ 'error'";
+    let report = json_report(&["--json", "--where", filter, &path]);
+    assert_eq!(report["entries"], 0);
+
+    let report = json_report(&["--json", "--keep-content-text", "--where", filter, &path]);
+    assert_eq!(report["entries"], 1);
+    assert_eq!(report["top_slowest"][0]["url"], "https://a.test/err");
+}
+
+#[test]
+fn invalid_where_expression_points_at_column() {
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["--where", "status >= 500 and timings.wiat > 100"])
+        .arg(fixture_path("chrome.har"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown field 'timings.wiat' at column 19",
+        ))
+        .stderr(predicate::str::contains(
+            "  status >= 500 and timings.wiat > 100\n                    ^",
+        ));
+}
+
+#[test]
+fn check_passes_within_budget() {
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["check", "--budget", &fixture_path("budget.toml")])
+        .arg(fixture_path("chrome.har"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "PASS  p95_time per host <= 500.00 ms  (actual www.example.org: 245.31 ms)",
+        ))
+        .stdout(predicate::str::contains(
+            "PASS  no request over 3s  (actual 245.31 ms)",
+        ))
+        .stdout(predicate::str::contains("5 of 5 rules passed"));
+}
+
+#[test]
+fn check_fails_on_budget_violations() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let budget = dir.path().join("budget.json");
+    std::fs::write(
+        &budget,
+        r#"{"rules": [
+            {"metric": "requests", "max": 2},
+            {"metric": "p95_time", "per": "host", "max": 100},
+            {"metric": "total_bytes", "max": "1MB"}
+        ]}"#,
+    )
+    .expect("write budget");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["check", "--budget"])
+        .arg(&budget)
+        .arg(fixture_path("chrome.har"))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("FAIL  requests <= 2  (actual 3)"))
+        .stdout(predicate::str::contains(
+            "FAIL  p95_time per host <= 100.00 ms  (actual www.example.org: 245.31 ms)\n        245.31 ms  www.example.org\n",
+        ))
+        .stdout(predicate::str::contains("PASS  total_bytes <= 1.00 MB"))
+        .stdout(predicate::str::contains("1 of 3 rules passed"))
+        .stderr(predicate::str::contains(
+            "budget check failed: 2 of 3 rules violated",
+        ));
+
+    let output = Command::cargo_bin("perf_tool")
+        .expect("binary should build")
+        .args(["check", "--json", "--budget"])
+        .arg(&budget)
+        .arg(fixture_path("chrome.har"))
+        .output()
+        .expect("run");
+    assert!(!output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(result["passed"], false);
+    assert_eq!(result["rules"][0]["measurements"][0]["actual"], 3.0);
+    assert_eq!(
+        result["rules"][1]["measurements"][1]["group"],
+        "api.example.org"
+    );
+    assert_eq!(result["rules"][1]["measurements"][1]["passed"], true);
+}
+
+#[test]
+fn invalid_budget_is_rejected() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let budget = dir.path().join("budget.toml");
+    std::fs::write(&budget, "[[rule]]\nmetric = \"latency\"\nmax = 1\n").expect("write budget");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["check", "--budget"])
+        .arg(&budget)
+        .arg(fixture_path("chrome.har"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse budget file"))
+        .stderr(predicate::str::contains("rule 1: unknown metric 'latency'"));
+}
+
+#[test]
+fn check_writes_junit_report() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let budget = dir.path().join("budget.json");
+    std::fs::write(
+        &budget,
+        r#"{"rules": [
+            {"metric": "requests", "max": 10},
+            {"metric": "p95_time", "per": "host", "max": "100ms"}
+        ]}"#,
+    )
+    .expect("write budget");
+    let junit = dir.path().join("budget.xml");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["check", "--budget"])
+        .arg(&budget)
+        .arg("--junit")
+        .arg(&junit)
+        .arg(fixture_path("chrome.har"))
+        .assert()
+        .failure();
+
+    let xml = std::fs::read_to_string(&junit).expect("junit report");
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains("<testsuites name=\"perf_tool budget\" tests=\"2\" failures=\"1\">"));
+    assert!(xml.contains("<testcase name=\"requests &lt;= 10\""));
+    assert!(xml.contains(
+        "<failure message=\"www.example.org: p95_time: actual 245.31 ms, expected at most 100.00 ms\""
+    ));
+    assert!(xml.ends_with("</testsuites>\n"));
+}
+
+#[test]
+fn diff_matches_requests_between_captures() {
+    let baseline = fixture_path("chrome.har");
+    let candidate = fixture_path("chrome_candidate.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["diff", &baseline, &candidate, "--group-by", "host"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "requests: 1 matched, 2 added, 2 removed",
+        ))
+        .stdout(predicate::str::contains(
+            "+67.09 ms         +0 B  GET https://www.example.org/  (245.31 -> 312.40 ms)",
+        ))
+        .stdout(predicate::str::contains(
+            "87.50 ms      256 B  POST https://api.example.org/v1/events",
+        ))
+        .stdout(predicate::str::contains("groups by host (3):"));
+
+    let report = json_report(&["diff", "--json", "--ignore-query", &baseline, &candidate]);
+    assert_eq!(report["matched_requests"], 2);
+    assert_eq!(report["added_requests"], 1);
+    assert_eq!(report["removed_requests"], 1);
+    assert_eq!(
+        report["changed"][1]["key"],
+        "GET https://www.example.org/static/app.js"
+    );
+    assert_eq!(report["changed"][1]["time_ms"]["baseline"], 198.644);
+    assert_eq!(report["changed"][1]["time_ms"]["candidate"], 150.2);
+    assert_eq!(report["totals"]["entries"]["delta"], 0);
+    assert_eq!(report["totals"]["total_bytes"]["delta"], 48210);
+    assert_eq!(
+        report["added"][0]["url"],
+        "https://cdn.example.net/fonts/inter.woff2"
+    );
+    assert_eq!(report["group_by"], serde_json::Value::Null);
+}
+
+#[test]
+fn compare_flags_significant_regressions_across_runs() {
+    let baseline = fixture_path("runs/baseline-*.har");
+    let candidate = fixture_path("runs/candidate-*.har");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args([
+        "compare",
+        "--baseline",
+        &baseline,
+        "--candidate",
+        &candidate,
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("baseline:  6 runs, 60 entries"))
+    .stdout(predicate::str::contains("regressions 2:"))
+    .stdout(predicate::str::contains("endpoint /api/items"))
+    .stdout(predicate::str::contains("host api.shop.test"))
+    .stdout(predicate::str::contains("improvements").not());
+
+    let report = json_report(&[
+        "compare",
+        "--json",
+        "--group-by",
+        "endpoint",
+        "--baseline",
+        &baseline,
+        "--candidate",
+        &candidate,
+    ]);
+    assert_eq!(report["tested_groups"], 4);
+    assert_eq!(report["untested_groups"], 18);
+    assert_eq!(report["regressions"].as_array().unwrap().len(), 1);
+    let items = &report["regressions"][0];
+    assert_eq!(items["key"], "/api/items");
+    assert_eq!(items["baseline"]["runs"], 6);
+    assert_eq!(items["candidate"]["runs"], 6);
+    assert_eq!(items["median_shift_ms"], 85.0);
+    assert_eq!(items["magnitude"], "large");
+    assert!(items["ci_low_ms"].as_f64().unwrap() > 0.0);
+    let p_value = items["p_value"].as_f64().unwrap();
+    let adjusted = items["adjusted_p_value"].as_f64().unwrap();
+    assert!(p_value < adjusted && adjusted < 0.05);
+
+    let same = json_report(&[
+        "compare",
+        "--json",
+        "--baseline",
+        &baseline,
+        "--candidate",
+        &baseline,
+    ]);
+    assert_eq!(same["regressions"].as_array().unwrap().len(), 0);
+    assert_eq!(same["improvements"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn suite_indexes_endpoints_across_tests() {
+    let runs = fixture_path("runs");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["suite", &runs, "--top", "1", "--list-tests"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tests: 12"))
+        .stdout(predicate::str::contains("endpoints (1):"))
+        .stdout(predicate::str::contains(
+            "48 req    12 tests    115.00 ms p50    199.00 ms p95    200.00 ms p99  /api/items",
+        ))
+        .stdout(predicate::str::contains("worst: "))
+        .stdout(predicate::str::contains("4 req    111.00 ms max  "));
+
+    let report = json_report(&["suite", "--json", "--percentiles", "50", &runs]);
+    assert_eq!(report["tests"], 12);
+    assert_eq!(report["report"]["entries"], 120);
+    // Four shared endpoints plus one per image.
+    assert_eq!(report["endpoints"].as_array().unwrap().len(), 22);
+    let items = &report["endpoints"][0];
+    assert_eq!(items["key"], "/api/items");
+    assert_eq!(items["count"], 48);
+    assert_eq!(items["percentiles"]["p50"], 115.0);
+    assert_eq!(items["tests"].as_array().unwrap().len(), 12);
+    assert_eq!(items["tests"][0]["requests"], 4);
+    assert!(items["worst_test"]["test"]
+        .as_str()
+        .unwrap()
+        .ends_with("candidate-3.har"));
+    assert_eq!(items["worst_test"]["max_time_ms"], 200.0);
+}
+
+#[test]
+fn record_and_trend_track_runs_over_time() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let store = dir.path().join("history").join("perf.jsonl");
+    let store_arg = store.to_string_lossy().into_owned();
+    let runs = [
+        ("baseline-1.har", "build-1", "2026-10-01T09:00:00Z"),
+        ("baseline-2.har", "build-2", "2026-10-02T09:00:00Z"),
+        ("candidate-1.har", "build-3", "2026-10-03T09:00:00Z"),
+    ];
+    // Recorded out of order; trend sorts by timestamp.
+    for (har, label, timestamp) in [runs[2], runs[0], runs[1]] {
+        let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+        cmd.args([
+            "record",
+            &fixture_path(&format!("runs/{}", har)),
+            "--label",
+            label,
+            "--sha",
+            "0a1b2c3",
+            "--timestamp",
+            timestamp,
+            "--store",
+            &store_arg,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("recorded {}", label)));
+    }
+    assert_eq!(
+        std::fs::read_to_string(&store)
+            .expect("read store")
+            .lines()
+            .count(),
+        3
+    );
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["trend", "--store", &store_arg])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("runs: 3 (build-1 .. build-3)"))
+        .stdout(predicate::str::contains(
+            "total_time              ▁▁█  1034.00 ms -> 1374.00 ms  (+32.9%)",
+        ))
+        .stdout(predicate::str::contains("p95_time api.shop.test  "));
+
+    let trend = json_report(&[
+        "trend",
+        "--json",
+        "--last",
+        "2",
+        "--host",
+        "api.shop.test",
+        "--store",
+        &store_arg,
+    ]);
+    assert_eq!(trend["runs"], 2);
+    let series = trend["series"].as_array().unwrap();
+    assert_eq!(series.len(), 4);
+    assert_eq!(series[3]["host"], "api.shop.test");
+    assert_eq!(series[3]["points"][0]["label"], "build-2");
+    assert_eq!(series[3]["points"][1]["sha"], "0a1b2c3");
+    assert_eq!(series[3]["points"][1]["value"], 199.0);
+}
+
+#[test]
+fn invalid_history_store_is_rejected() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let store = dir.path().join("perf.jsonl");
+    std::fs::write(&store, "not json\n").expect("write store");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["trend", "--store", &store.to_string_lossy()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse history store"))
+        .stderr(predicate::str::contains("line 1"));
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args([
+        "trend",
+        "--store",
+        &dir.path().join("missing.jsonl").to_string_lossy(),
+    ])
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("failed to read history store"));
+}
+
+#[test]
+fn html_format_writes_self_contained_report() {
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    let output = cmd
+        .args([
+            "--format",
+            "html",
+            "--group-by",
+            "host",
+            &fixture_path("chrome.har"),
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let html = String::from_utf8(output).expect("utf-8 output");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.trim_end().ends_with("</html>"));
+    // Styles and scripts are inline so the page opens without network access.
+    assert!(html.contains("<style>") && html.contains("<script>"));
+    assert!(!html.contains("<link") && !html.contains(" src="));
+    assert!(html.contains("<h3>Slowest requests (3)</h3>"));
+    assert!(html.contains("<table class=\"sortable\">"));
+    assert!(html.contains("<h3>Groups by host (2)</h3>"));
+    assert!(html.contains("<h3>Timing phases</h3>"));
+    assert!(html.contains("<h3>Waterfall (3 requests, 681.50 ms)</h3>"));
+    assert!(html.contains("POST https://api.example.org/v1/events (204): starts at 594.00 ms"));
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["--json", "--format", "html", &fixture_path("chrome.har")])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    let report = json_report(&["--format", "json", &fixture_path("chrome.har")]);
+    assert_eq!(report["entries"], 3);
+    assert_eq!(report["waterfall"], serde_json::json!([]));
+}
+
+#[test]
+fn markdown_format_renders_tables_for_pull_requests() {
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args([
+        "--format",
+        "markdown",
+        "--group-by",
+        "host",
+        &fixture_path("chrome.har"),
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::starts_with(
+        "### perf_tool report: tests/fixtures/chrome.har\n\n| metric | value |\n|---|---:|\n",
+    ))
+    .stdout(predicate::str::contains("| total bytes | 146.94 KB |\n"))
+    .stdout(predicate::str::contains(
+        "<details>\n<summary>Slowest requests (3)</summary>\n\n| # | URL | time | bytes | TTFB |\n",
+    ))
+    .stdout(predicate::str::contains(
+        "| 2 | `https://www.example.org/static/app.js?v=7f3a9c` | 198.64 ms | 128.38 KB | 62.26 ms |\n",
+    ))
+    .stdout(predicate::str::contains(
+        "| `api.example.org` | 1 | 87.50 ms | 87.50 ms | 87.50 ms | 256 B |\n",
+    ));
+
+    let dir = tempfile::tempdir().expect("temp dir");
+    let budget = dir.path().join("budget.json");
+    std::fs::write(
+        &budget,
+        r#"{"rules": [
+            {"metric": "requests", "max": 2},
+            {"metric": "p95_time", "per": "host", "max": 100}
+        ]}"#,
+    )
+    .expect("write budget");
+
+    let mut cmd = Command::cargo_bin("perf_tool").expect("binary should build");
+    cmd.args(["check", "--format", "markdown", "--budget"])
+        .arg(&budget)
+        .arg(fixture_path("chrome.har"))
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with(
+            "### Performance budget: failed (2 of 2 rules violated)\n",
+        ))
+        .stdout(predicate::str::contains(
+            "| **FAIL** | p95\\_time per host \\<= 100.00 ms | `www.example.org`: 245.31 ms | 100.00 ms |\n",
+        ))
+        .stdout(predicate::str::contains("<summary>Violations (2)</summary>"))
+        .stderr(predicate::str::contains("budget check failed"));
+}